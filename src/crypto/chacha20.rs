@@ -0,0 +1,65 @@
+//! ChaCha20-Poly1305 and XChaCha20-Poly1305 AEAD encryptors.
+//!
+//! These provide a constant-time software cipher alternative to AES-GCM for
+//! platforms without AES hardware acceleration. `XChaCha20Poly1305Encryptor`
+//! additionally extends the nonce to 24 bytes, making random nonce generation
+//! safe even for very large numbers of messages under the same key.
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+
+use crate::crypto::Encryptor;
+use crate::error::{CryptorError, Result};
+
+/// Nonce length for ChaCha20-Poly1305 (96 bits)
+pub const CHACHA20_NONCE_LEN: usize = 12;
+
+/// Nonce length for XChaCha20-Poly1305 (192 bits)
+pub const XCHACHA20_NONCE_LEN: usize = 24;
+
+/// ChaCha20-Poly1305 AEAD encryptor (12-byte nonce)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChaCha20Poly1305Encryptor;
+
+impl Encryptor for ChaCha20Poly1305Encryptor {
+    fn encrypt(&self, key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let cipher = ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid ChaCha20 key: {}", e)))?;
+        cipher
+            .encrypt(nonce.into(), plaintext)
+            .map_err(|e| CryptorError::Cryptography(format!("ChaCha20-Poly1305 encryption failed: {}", e)))
+    }
+
+    fn decrypt(&self, key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let cipher = ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid ChaCha20 key: {}", e)))?;
+        cipher
+            .decrypt(nonce.into(), ciphertext)
+            .map_err(|e| CryptorError::Cryptography(format!("ChaCha20-Poly1305 decryption failed: {}", e)))
+    }
+}
+
+/// XChaCha20-Poly1305 AEAD encryptor (24-byte extended nonce)
+///
+/// The extended nonce makes this cipher misuse-resistant: random nonces can
+/// be generated per-message without a meaningful risk of collision.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XChaCha20Poly1305Encryptor;
+
+impl Encryptor for XChaCha20Poly1305Encryptor {
+    fn encrypt(&self, key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let cipher = XChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid XChaCha20 key: {}", e)))?;
+        cipher
+            .encrypt(nonce.into(), plaintext)
+            .map_err(|e| CryptorError::Cryptography(format!("XChaCha20-Poly1305 encryption failed: {}", e)))
+    }
+
+    fn decrypt(&self, key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let cipher = XChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid XChaCha20 key: {}", e)))?;
+        cipher
+            .decrypt(nonce.into(), ciphertext)
+            .map_err(|e| CryptorError::Cryptography(format!("XChaCha20-Poly1305 decryption failed: {}", e)))
+    }
+}
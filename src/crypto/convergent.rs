@@ -0,0 +1,326 @@
+//! Convergent (content-addressed) chunk encryption.
+//!
+//! Ordinary streaming encryption (see [`crate::crypto::streaming`]) derives
+//! every chunk's nonce from one random base nonce generated per stream, so
+//! the same plaintext chunk encrypted twice — even in the same file —
+//! produces different ciphertext. That's good for confidentiality, but it
+//! means identical chunks across different files can never be deduplicated
+//! in storage: they simply don't look alike.
+//!
+//! Convergent encryption (the model used by asuran and self_encryption)
+//! fixes that by deriving everything about a chunk's encryption from its
+//! own plaintext instead of from randomness:
+//!
+//! 1. `content_hash = HMAC-SHA256(master_key, plaintext_chunk)` — this is
+//!    both the chunk's storage address and the seed for its key material.
+//! 2. The chunk's AEAD key and nonce are derived from `content_hash` via
+//!    HKDF-SHA256, so they never need to be stored or transmitted.
+//! 3. A [`DataMap`] records, per chunk, the hash plus its original
+//!    offset/length, so a store that keeps chunks keyed by `content_hash`
+//!    holds a chunk shared by two files once, and a file can be reassembled
+//!    by looking up each hash in turn. This module provides no such store:
+//!    the streaming writer still writes convergent chunks as sequential,
+//!    indexed chunk records.
+//!
+//! # Privacy trade-off
+//!
+//! This is opt-in (see [`crate::crypto::streaming::StreamConfig::convergent`])
+//! and off by default. Keying the hash with `master_key` means an attacker
+//! without that key can't compute a chunk's address from a guessed
+//! plaintext — but anyone who *does* have the master key (e.g. every file
+//! sharing it within one volume) can still mount a confirmation-of-file
+//! attack: hash a candidate plaintext and check whether the resulting
+//! address already exists in the store. Convergent encryption should only
+//! be enabled when every party with the master key is equally trusted with
+//! that information, and never for volumes where unrelated users share one
+//! master key expecting mutual confidentiality.
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::crypto::streaming::{open_chunk, seal_chunk};
+use crate::error::{CryptorError, Result};
+use crate::volume::header::CipherAlgorithm;
+use std::io::{Read, Write};
+
+/// Length in bytes of a chunk's content hash (HMAC-SHA256 output).
+pub const CONTENT_HASH_LEN: usize = 32;
+
+/// HKDF `info` label for deriving a convergent chunk's AEAD key.
+const KEY_INFO: &[u8] = b"tesseract-convergent-chunk-key-v1";
+
+/// HKDF `info` label for deriving a convergent chunk's AEAD nonce.
+const NONCE_INFO: &[u8] = b"tesseract-convergent-chunk-nonce-v1";
+
+/// Computes a chunk's content hash: `HMAC-SHA256(master_key, plaintext)`.
+///
+/// This is both the chunk's dedup address and, via [`derive_chunk_key_material`],
+/// the seed for its encryption key and nonce. Keying the hash with
+/// `master_key` (rather than hashing the plaintext alone) means only
+/// someone who holds the master key can compute — or look up — a chunk's
+/// address.
+pub fn chunk_content_hash(master_key: &[u8], plaintext: &[u8]) -> [u8; CONTENT_HASH_LEN] {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(master_key).expect("HMAC-SHA256 accepts any key length");
+    mac.update(plaintext);
+    let mut out = [0u8; CONTENT_HASH_LEN];
+    out.copy_from_slice(&mac.finalize().into_bytes());
+    out
+}
+
+/// Derives a chunk's AEAD key and nonce from its content hash via
+/// HKDF-SHA256, one `expand` per output with a distinct `info` label so the
+/// two never collide.
+fn derive_chunk_key_material(
+    content_hash: &[u8; CONTENT_HASH_LEN],
+    nonce_len: usize,
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>)> {
+    let hkdf = Hkdf::<Sha256>::new(None, content_hash);
+
+    let mut key = Zeroizing::new(vec![0u8; 32]);
+    hkdf.expand(KEY_INFO, &mut key)
+        .map_err(|e| CryptorError::Cryptography(format!("HKDF key expansion failed: {}", e)))?;
+
+    let mut nonce = vec![0u8; nonce_len];
+    hkdf.expand(NONCE_INFO, &mut nonce)
+        .map_err(|e| CryptorError::Cryptography(format!("HKDF nonce expansion failed: {}", e)))?;
+
+    Ok((key, nonce))
+}
+
+/// Encrypts one chunk convergently: its key, nonce, and storage address are
+/// all derived from `plaintext` itself (via `master_key`), so encrypting
+/// the same plaintext chunk again — in this stream or any other sharing
+/// `master_key` — reproduces the exact same ciphertext and address.
+///
+/// Returns `(content_hash, ciphertext)`. `content_hash` both addresses the
+/// chunk in storage and is bound into the AEAD associated data, so a
+/// ciphertext stored under one address cannot be silently relabeled to
+/// another.
+pub fn encrypt_chunk_convergent(
+    cipher: CipherAlgorithm,
+    master_key: &[u8],
+    plaintext: &[u8],
+) -> Result<([u8; CONTENT_HASH_LEN], Vec<u8>)> {
+    let content_hash = chunk_content_hash(master_key, plaintext);
+    let (key, nonce) = derive_chunk_key_material(&content_hash, cipher.nonce_len())?;
+    let ciphertext = seal_chunk(cipher, &key, &nonce, plaintext, &content_hash)?;
+    Ok((content_hash, ciphertext))
+}
+
+/// Decrypts one convergently-encrypted chunk given its address and stored
+/// ciphertext, re-deriving the key/nonce from `content_hash` alone — note
+/// that, unlike [`encrypt_chunk_convergent`], decryption itself does not
+/// require `master_key` at all; anyone holding `content_hash` and the
+/// ciphertext can decrypt. `master_key` is used only to verify that the
+/// decrypted plaintext actually hashes back to `content_hash`, catching a
+/// chunk that was stored under (or substituted for) the wrong address.
+pub fn decrypt_chunk_convergent(
+    cipher: CipherAlgorithm,
+    master_key: &[u8],
+    content_hash: &[u8; CONTENT_HASH_LEN],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>> {
+    let (key, nonce) = derive_chunk_key_material(content_hash, cipher.nonce_len())?;
+    let plaintext = open_chunk(cipher, &key, &nonce, ciphertext, content_hash)?;
+
+    if chunk_content_hash(master_key, &plaintext) != *content_hash {
+        return Err(CryptorError::Cryptography(
+            "chunk plaintext does not match its content hash".to_string(),
+        ));
+    }
+
+    Ok(plaintext)
+}
+
+/// One entry in a [`DataMap`]: where one chunk of the original file sat,
+/// addressed by its content hash rather than by position in a chunk store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataMapEntry {
+    /// Content hash of this chunk, as produced by [`chunk_content_hash`]
+    pub content_hash: [u8; CONTENT_HASH_LEN],
+    /// Byte offset of this chunk within the original (plaintext) file
+    pub offset: u64,
+    /// Length in bytes of this chunk's plaintext
+    pub length: u32,
+}
+
+/// Reconstruction map for a convergently-encrypted file: the ordered list
+/// of chunk hashes (and their original offsets/lengths) needed to fetch
+/// each chunk from a content-addressed store and reassemble the plaintext.
+/// A [`StreamHeader`](crate::crypto::streaming::StreamHeader) neither embeds
+/// nor references one; [`DataMap::write_to`] serializes it on its own.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataMap {
+    /// Chunks in original-file order
+    pub entries: Vec<DataMapEntry>,
+}
+
+impl DataMap {
+    /// Writes the data map as `count: u32` followed by that many
+    /// `(content_hash: 32 bytes, offset: u64, length: u32)` entries.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+        for entry in &self.entries {
+            writer.write_all(&entry.content_hash)?;
+            writer.write_all(&entry.offset.to_le_bytes())?;
+            writer.write_all(&entry.length.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a data map written by [`DataMap::write_to`].
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut count_bytes = [0u8; 4];
+        reader.read_exact(&mut count_bytes)?;
+        let count = u32::from_le_bytes(count_bytes) as usize;
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut content_hash = [0u8; CONTENT_HASH_LEN];
+            reader.read_exact(&mut content_hash)?;
+
+            let mut offset_bytes = [0u8; 8];
+            reader.read_exact(&mut offset_bytes)?;
+
+            let mut length_bytes = [0u8; 4];
+            reader.read_exact(&mut length_bytes)?;
+
+            entries.push(DataMapEntry {
+                content_hash,
+                offset: u64::from_le_bytes(offset_bytes),
+                length: u32::from_le_bytes(length_bytes),
+            });
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_content_hash_deterministic() {
+        let key = b"master key bytes";
+        let hash_a = chunk_content_hash(key, b"hello world");
+        let hash_b = chunk_content_hash(key, b"hello world");
+        assert_eq!(hash_a, hash_b);
+    }
+
+    #[test]
+    fn test_chunk_content_hash_differs_by_plaintext() {
+        let key = b"master key bytes";
+        assert_ne!(
+            chunk_content_hash(key, b"hello world"),
+            chunk_content_hash(key, b"goodbye world")
+        );
+    }
+
+    #[test]
+    fn test_chunk_content_hash_differs_by_key() {
+        let plaintext = b"identical plaintext";
+        assert_ne!(
+            chunk_content_hash(b"key one", plaintext),
+            chunk_content_hash(b"key two", plaintext)
+        );
+    }
+
+    #[test]
+    fn test_convergent_roundtrip() {
+        let master_key = b"a 32-byte-ish master key.......!";
+        let plaintext = b"identical chunks across files dedupe";
+
+        let (hash, ciphertext) =
+            encrypt_chunk_convergent(CipherAlgorithm::Aes256Gcm, master_key, plaintext).unwrap();
+        let decrypted =
+            decrypt_chunk_convergent(CipherAlgorithm::Aes256Gcm, master_key, &hash, &ciphertext)
+                .unwrap();
+
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_convergent_is_deterministic_across_encryptions() {
+        // The entire point: encrypting the same plaintext chunk twice (as
+        // if it appeared in two different files) produces byte-identical
+        // ciphertext and the same address, so a store can dedupe it.
+        let master_key = b"shared volume master key!!!!!!!";
+        let plaintext = b"this chunk appears in two different files";
+
+        let (hash_a, ciphertext_a) =
+            encrypt_chunk_convergent(CipherAlgorithm::XChaCha20Poly1305, master_key, plaintext)
+                .unwrap();
+        let (hash_b, ciphertext_b) =
+            encrypt_chunk_convergent(CipherAlgorithm::XChaCha20Poly1305, master_key, plaintext)
+                .unwrap();
+
+        assert_eq!(hash_a, hash_b);
+        assert_eq!(ciphertext_a, ciphertext_b);
+    }
+
+    #[test]
+    fn test_convergent_distinct_plaintexts_dont_collide() {
+        let master_key = b"another master key for this test";
+
+        let (hash_a, ciphertext_a) =
+            encrypt_chunk_convergent(CipherAlgorithm::ChaCha20Poly1305, master_key, b"chunk A")
+                .unwrap();
+        let (hash_b, ciphertext_b) =
+            encrypt_chunk_convergent(CipherAlgorithm::ChaCha20Poly1305, master_key, b"chunk B")
+                .unwrap();
+
+        assert_ne!(hash_a, hash_b);
+        assert_ne!(ciphertext_a, ciphertext_b);
+    }
+
+    #[test]
+    fn test_convergent_rejects_chunk_under_wrong_address() {
+        let master_key = b"yet another master key material!";
+        let (_, ciphertext_a) =
+            encrypt_chunk_convergent(CipherAlgorithm::Aes256Gcm, master_key, b"chunk A").unwrap();
+        let (hash_b, _) =
+            encrypt_chunk_convergent(CipherAlgorithm::Aes256Gcm, master_key, b"chunk B").unwrap();
+
+        // Ciphertext for "chunk A" stored/retrieved under "chunk B"'s
+        // address: AEAD decryption itself should fail, since the address
+        // is bound into the associated data.
+        let result =
+            decrypt_chunk_convergent(CipherAlgorithm::Aes256Gcm, master_key, &hash_b, &ciphertext_a);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_data_map_roundtrip() {
+        let map = DataMap {
+            entries: vec![
+                DataMapEntry { content_hash: [1u8; CONTENT_HASH_LEN], offset: 0, length: 4096 },
+                DataMapEntry { content_hash: [2u8; CONTENT_HASH_LEN], offset: 4096, length: 2048 },
+            ],
+        };
+
+        let mut buffer = Vec::new();
+        map.write_to(&mut buffer).unwrap();
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        let decoded = DataMap::read_from(&mut cursor).unwrap();
+
+        assert_eq!(map, decoded);
+    }
+
+    #[test]
+    fn test_data_map_empty_roundtrip() {
+        let map = DataMap::default();
+
+        let mut buffer = Vec::new();
+        map.write_to(&mut buffer).unwrap();
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        let decoded = DataMap::read_from(&mut cursor).unwrap();
+
+        assert_eq!(map, decoded);
+    }
+}
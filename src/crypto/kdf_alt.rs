@@ -0,0 +1,81 @@
+//! Alternative key-derivation functions to Argon2id.
+//!
+//! These exist for devices where Argon2's memory cost is impractical (e.g.
+//! low-memory embedded targets) or for interoperability with tools that
+//! standardized on PBKDF2 or scrypt.
+
+use pbkdf2::pbkdf2_hmac;
+use scrypt::Params as ScryptParams;
+use sha2::Sha256;
+
+use crate::crypto::KeyDerivation;
+use crate::error::{CryptorError, Result};
+
+/// Length of derived keys in bytes (256-bit)
+const KEY_LEN: usize = 32;
+
+/// PBKDF2-HMAC-SHA256 key derivation
+#[derive(Debug, Clone, Copy)]
+pub struct Pbkdf2Kdf {
+    iterations: u32,
+}
+
+impl Pbkdf2Kdf {
+    /// Creates a new PBKDF2-HMAC-SHA256 KDF with the given iteration count
+    pub fn new(iterations: u32) -> Self {
+        Self { iterations }
+    }
+}
+
+impl KeyDerivation for Pbkdf2Kdf {
+    fn derive_key(&self, password: &[u8], salt: &[u8]) -> Result<Vec<u8>> {
+        let mut key = vec![0u8; KEY_LEN];
+        pbkdf2_hmac::<Sha256>(password, salt, self.iterations, &mut key);
+        Ok(key)
+    }
+
+    fn generate_salt(&self) -> [u8; 32] {
+        let mut salt = [0u8; 32];
+        getrandom::fill(&mut salt).expect("failed to generate random salt");
+        salt
+    }
+}
+
+/// scrypt key derivation
+#[derive(Debug, Clone, Copy)]
+pub struct ScryptKdf {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+impl ScryptKdf {
+    /// Creates a new scrypt KDF with the given cost parameters
+    ///
+    /// # Arguments
+    ///
+    /// * `log_n` - CPU/memory cost as a power of two (N = 2^log_n)
+    /// * `r` - Block size parameter
+    /// * `p` - Parallelization parameter
+    pub fn new(log_n: u8, r: u32, p: u32) -> Self {
+        Self { log_n, r, p }
+    }
+}
+
+impl KeyDerivation for ScryptKdf {
+    fn derive_key(&self, password: &[u8], salt: &[u8]) -> Result<Vec<u8>> {
+        let params = ScryptParams::new(self.log_n, self.r, self.p, KEY_LEN)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid scrypt parameters: {}", e)))?;
+
+        let mut key = vec![0u8; KEY_LEN];
+        scrypt::scrypt(password, salt, &params, &mut key)
+            .map_err(|e| CryptorError::Cryptography(format!("scrypt derivation failed: {}", e)))?;
+        Ok(key)
+    }
+
+    fn generate_salt(&self) -> [u8; 32] {
+        let mut salt = [0u8; 32];
+        getrandom::fill(&mut salt).expect("failed to generate random salt");
+        salt
+    }
+}
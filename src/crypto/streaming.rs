@@ -21,7 +21,8 @@
 //!   - Salt length: u16 (2 bytes)
 //!   - Salt: variable
 //!   - Base nonce: 12 bytes
-//!   - Chunk size: u32 (4 bytes)
+//!   - Chunking strategy tag: u8 (0 = Fixed, 1 = FastCdc)
+//!   - Chunking parameters: 1 or 3 u32s, depending on the tag above
 //!   - Total chunks: u64 (8 bytes)
 //!   - Original file size: u64 (8 bytes)
 //!   - Metadata size: u16 (2 bytes)
@@ -39,9 +40,27 @@
 //!
 //! ...
 //! ```
+//!
+//! Chunks are written as [`ChunkRecord`]s, whose layout (magic tag, CRC-32,
+//! chunk index, compression tag, ciphertext length, ciphertext) replaces the
+//! two-field chunk sketch above, and the header ends with the chunk offset
+//! index (see [`StreamHeader::chunk_index`]) after the metadata.
+//!
+//! Each chunk record carries its own length, so under
+//! [`ChunkingStrategy::FastCdc`] the header's chunking parameters are only
+//! needed to re-run the same cut points for deduplication comparisons; a
+//! reader just trusts the per-chunk size when reassembling the plaintext.
+
+use aes_gcm::aead::{Aead as _, KeyInit as _, Payload as AesGcmPayload};
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::aead::Payload as ChaChaPayload;
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+use zeroize::Zeroizing;
 
 use crate::config::NONCE_LEN;
+use crate::crypto::convergent::{encrypt_chunk_convergent, DataMap, DataMapEntry};
 use crate::error::{CryptorError, Result};
+use crate::volume::header::CipherAlgorithm;
 use std::io::{Read, Write};
 
 /// Default chunk size: 1 MB
@@ -59,26 +78,298 @@ pub const MAGIC_BYTES_V2: &[u8] = b"SCRYPTv2";
 /// File format version
 pub const FORMAT_VERSION: u8 = 0x02;
 
+/// How a plaintext stream is cut into chunks.
+///
+/// `Fixed` always cuts at the same byte offset, which means inserting or
+/// removing a single byte near the start of the stream shifts every
+/// subsequent chunk boundary. `FastCdc` instead cuts where the data itself
+/// looks a certain way (see [`fastcdc_next_cut`]), so an edit only disturbs
+/// the chunk(s) around it — identical regions of two different streams
+/// still produce identical chunks, which is what downstream deduplication
+/// needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkingStrategy {
+    /// Every chunk is exactly this many bytes, except possibly the last.
+    Fixed(usize),
+    /// Content-defined chunking: chunks are never smaller than `min`, never
+    /// larger than `max`, and average out to roughly `avg` bytes.
+    FastCdc {
+        min: usize,
+        avg: usize,
+        max: usize,
+    },
+}
+
+impl ChunkingStrategy {
+    /// A target/average chunk size for callers that just need a single
+    /// number, e.g. to size a read buffer or estimate a chunk count.
+    pub fn target_size(&self) -> usize {
+        match *self {
+            ChunkingStrategy::Fixed(size) => size,
+            ChunkingStrategy::FastCdc { avg, .. } => avg,
+        }
+    }
+
+    /// Tag byte used to distinguish variants in the V2 header.
+    fn tag(&self) -> u8 {
+        match self {
+            ChunkingStrategy::Fixed(_) => 0,
+            ChunkingStrategy::FastCdc { .. } => 1,
+        }
+    }
+
+    /// Length of the next chunk to cut from the start of `data`.
+    ///
+    /// For [`ChunkingStrategy::Fixed`] this is just `min(chunk_size,
+    /// data.len())`. For [`ChunkingStrategy::FastCdc`] the boundary is
+    /// content-defined (see [`fastcdc_next_cut`]), so identical regions in
+    /// different streams cut identically regardless of what precedes them.
+    pub fn next_chunk_len(&self, data: &[u8]) -> usize {
+        match *self {
+            ChunkingStrategy::Fixed(size) => size.min(data.len()),
+            ChunkingStrategy::FastCdc { min, avg, max } => fastcdc_next_cut(data, min, avg, max),
+        }
+    }
+
+    /// How much plaintext a streaming writer must have buffered before it
+    /// can ask [`ChunkingStrategy::next_chunk_len`] for a real cut, rather
+    /// than one that's only short because the writer hasn't been fed enough
+    /// input yet. For `Fixed` that's the chunk size itself; for `FastCdc`
+    /// it's `max`, since [`fastcdc_next_cut`] can legitimately run all the
+    /// way out to `max` before finding a boundary.
+    fn min_lookahead(&self) -> usize {
+        match *self {
+            ChunkingStrategy::Fixed(size) => size,
+            ChunkingStrategy::FastCdc { max, .. } => max,
+        }
+    }
+}
+
+/// "Gear" table for FastCDC's rolling fingerprint: 256 fixed pseudorandom
+/// u64 values, one per possible input byte. The table is arbitrary but
+/// fixed — every encryptor and decryptor must agree on it, since it
+/// determines where chunk boundaries fall.
+#[rustfmt::skip]
+const GEAR: [u64; 256] = [
+    0xD70A8547C2BE45CA, 0xA50734A982C91B2C, 0xE7797112075E8485, 0x1297AAEFD0A33F0C,
+    0x985CE5A4DE850025, 0x3F0D9FEC110B145B, 0xE33B17F63CF9870A, 0xDC066C5DA4B3076B,
+    0x5B72E2E7A4E60DCF, 0x7DABF1E76EB92500, 0x1C6BD6F17E832372, 0x2ECDD98C40F82EE9,
+    0x5CF3C99C3B715E2A, 0x6BD2B9EC3AF9A020, 0x70AF127219C62C17, 0x84C11C7C78A59136,
+    0x41DA54A3FD70546A, 0x4B25715860A6B551, 0x46DA93563B4D02CA, 0x135104FF9F365856,
+    0xE1638A89873D9135, 0x5F84C0AB71439D18, 0x0DF3737E0A7115E8, 0xCDB8E768395E22DB,
+    0x31C0E38DF2CB64D9, 0xF7FA552095ABED38, 0xB550D8B551847B8B, 0xA9C022FE96895351,
+    0x24BB66C94E8F5166, 0xB8E5B9AF0B23B928, 0x45546E5E6CD62161, 0x87EAC5B1D89213DF,
+    0xB266AB7B8BD45086, 0xD033C8D26067C8A8, 0x269D6156F0AF0353, 0x277110B2AA036581,
+    0x776FBFC0B5B55F65, 0x41060877E92003AD, 0x4A24F62063AD539D, 0x53CE5B3FF30D4201,
+    0x221877618461A3CD, 0x8B8F7A0F6B5BC56F, 0x963FDB480DC4BAB0, 0xFB609AA63CCF0C4A,
+    0x143AAA9F3C1228F6, 0x6502E38981243BA1, 0x108E060C09B26F2E, 0xF9134CB3523459D7,
+    0x80CF000CDDC33A1C, 0x7FD9596D6232D3AD, 0xEE4BEB0F2014AC87, 0x83892BBCD56E22CF,
+    0x1A582848629DD82C, 0x9F82605D2A42C435, 0xF8FA69278B413F7E, 0x182634C473F3172F,
+    0x180162FDFB793999, 0x31251815DE7BD3DB, 0x57CECF0FA3D860E7, 0xBF543C3268C4B13B,
+    0x1407BB01F361213E, 0xDCC42C27E12DD5BD, 0x594360D1D6AFA741, 0xF64E7BFEA7929C2F,
+    0x64A8B973A22C42EC, 0xE919A8C6E879D78E, 0x0CBB0316AFB28089, 0x46625F46F2C666F8,
+    0x271488BC3B3D3A6A, 0x5FA83A157ACA76AC, 0xB128EDF6F05F34C1, 0xEF4E22112F77369D,
+    0x40CFCC04F4A2BC4C, 0xDAB3395E6BA7D41C, 0x68B1DE3478259A8B, 0x7638903805C95664,
+    0x7726EA8FB5B3D1EA, 0x43EB9DAA8F484C69, 0x7852FC73289C4150, 0xED92789C193B1729,
+    0x0E7631EF6C0DB273, 0xC9110DDDE4FB2C27, 0x1B4DFDCEDC9C24B0, 0xE78B4E4A96B21D98,
+    0xF85B94821EBD3D6C, 0x29DA91AFABDB0BB9, 0xB7E1ABB43D5E97B7, 0x847845561C71D555,
+    0xB28CAE74E56F7008, 0xB002DDD358D49007, 0xDFE1B32DA7A36273, 0x7B63D67D11B2105B,
+    0x0ED3B17B0A813397, 0xE18AE03AADB2DE83, 0x9484E748E7E12CB2, 0x289E5246F973D48C,
+    0x5CEA13F75E3019C6, 0x971BE0E549F799AE, 0x363CC86DB45E6472, 0xA301092A9106410A,
+    0xD1E403DAC82E6698, 0x22C0B0070DF17E1E, 0xAFF82924D31ED5C5, 0x60BA58EFB5BE3DFA,
+    0xB075BF227C82F8BB, 0x3882B4E75B28E73D, 0x6303B0FA161D9BE2, 0x0C1529DB44A0C9BC,
+    0xE714DF84796588EF, 0x1ACDE68F906A51E7, 0xCD44C0A4E8B09A6B, 0x48457E5EF2F1DB37,
+    0xF7CDA6D8D8411E61, 0xFAFA344119364D1A, 0x58CB67BB31CDD031, 0xEBF795A9AAB75C3E,
+    0x88DA105536305ADA, 0x5BFEFD3EC15EB37F, 0x0AE656797422DC5B, 0x16A500ED725581EB,
+    0xE884E4B825E74182, 0x2B599A3046E4D814, 0x0468036D984D4C13, 0x8A1452090452F1B7,
+    0x455FA9CC36D93448, 0x8D26BD1ED0CA0D84, 0x37D68037853DB83E, 0x40375374BFB54594,
+    0xAD9AAF789B51F3E9, 0x36463659C765E2D3, 0x88742A73EF03C972, 0x0AF7326B1BCF6B2E,
+    0xFCFB0B6319D0AE68, 0x50FA4FC5C73D6F7F, 0x73653DEE00140ADA, 0x6D1ABBC34EC74FD3,
+    0xAF45E20DF377C335, 0xB5323E8158846091, 0x85331509A8A5E7CD, 0x0F82D28660422600,
+    0xDFD88573859E4527, 0x48B565E6A552DF0D, 0x6BF14E3B274DC0DA, 0x5BE7F3D0D8152ADF,
+    0x2656D67CA9EB24CC, 0x951B5620D3C46B6A, 0x0B9B31CBEAEDD069, 0xB48C7E96D4216C2A,
+    0x2718E329CC554946, 0xBB7CD7E45E316CB0, 0x212CE8051326D4A7, 0xA16472BA99686F68,
+    0xBC0F814F55D274A6, 0x3AA036BFD36715A7, 0x10CF6B79F29F9EA6, 0x416B5BCFD1E6872A,
+    0x4BAFE26FAF71AA5E, 0x29A8D9B37CB85CB1, 0xCB42580DF06B5206, 0x2C8B81A6AA093C87,
+    0x5D131D0CCFA5E3F8, 0xC13812D1BD0EF7DF, 0x3E9C28D692EA79CC, 0x612F5706B08E9DA0,
+    0x39ADE2DAB58D3835, 0x4258D362918407B5, 0xDDFC96CA8B43BB0E, 0x14F293137057B8FD,
+    0x1AF8976FA3A22E75, 0xCAC29F0EF10ECE05, 0x5AA2CD9CBAAF1717, 0x7EFC1E8AE94E2FBC,
+    0xA095061F1C52ECE9, 0x30E5E6B9C623EE8E, 0x61C92B19557FF6B7, 0xBF612E1C975494EE,
+    0x81121A05A87C11F6, 0x0C19DE2C06758470, 0x3C9E4767563B1A0A, 0xAE23697C1D4C27D5,
+    0x68F0BF1C038A38DA, 0xECAA4B7EF6247FD4, 0xEF1676AC918F2D0E, 0xCCA8890BC45EC8CB,
+    0x50B77AF4F7BAF78D, 0x7925CDC5D39F3706, 0xF3D9C867195D63CA, 0x1BB55CC2073F3E04,
+    0x8131FB25B2EA22C9, 0x5DD013A07396B1E0, 0x234F88DFF2827C1F, 0xCD8572DE4CA35B20,
+    0xF5038C445331CEAB, 0xEFDE26256A60C095, 0x750D02755661EBF6, 0x17303F478E2CD98D,
+    0xF84A01FC18CF374F, 0xD3016B6BE1A8A3F6, 0x4D0C1184F04C00B8, 0x2EEAC0B7F8E120E6,
+    0x974C66B49D29C929, 0xAE24D94AF4D3B637, 0x9E2F223C879BA2D6, 0x4E09505D71F163AD,
+    0xE0C6A09CC18C9EB7, 0x9A8042E2BAF7DF7E, 0x5B61B7AE7271D7DE, 0xAFF2B07FD4F40D63,
+    0x8FCFA0EC33D151BD, 0x51C62C0271431F38, 0xFD8206B7202A4539, 0x3941107D11A5E513,
+    0xDF401E7476987E75, 0x6C3C3E05AA095C02, 0xCCC4548FB954BB7D, 0xB213E49B0420638D,
+    0x18ADCB399A637A6C, 0x5B15F986FF95CA3D, 0x83E52A9D50339DA1, 0xB28C4D090D85621D,
+    0x35C4BC0FDDD9209D, 0x322D164880FB45CC, 0xA595006DB22B8D77, 0x343098F6D1A994D6,
+    0x9EDC0ECFC67B4C31, 0xBEAFEA710C84EBA1, 0xB15CFA0618B4F47F, 0xF3335674406D3F32,
+    0x1DC85E02B6583D1F, 0xCBDEE87654807381, 0x62C29F350EF05B4A, 0x74CB09C0132A9185,
+    0x1F623F154468281F, 0x9207DF3B78BBAD55, 0x5182A2519530B999, 0xFB81FABCC68A39FE,
+    0x836AA41A65C36697, 0xBD993351DA2FCD9D, 0xD007AA5955579282, 0xAB4701B09FA41F96,
+    0xE53A18FFA64E87C2, 0xD4E00272423FE4F1, 0x40EEB09D3FCD20DA, 0x6775A0877E600460,
+    0xB1920B08E78272BB, 0x1F462CDB70C55160, 0xD3067D39F30A4E4D, 0x2388D70DAA2BBE5F,
+    0xA39474E875EA04F4, 0xE9D0946F7310F462, 0x8BEF762030A02ABC, 0xC97B788BBDA5C254,
+    0x844913580E15CB84, 0x253ED984D0FB2EA7, 0xDFDDABFB8445B536, 0xFDC40AEC0BADA4F2,
+];
+
+/// A low-bits mask with `bits` ones, used to test the rolling fingerprint
+/// against in FastCDC. `bits` is clamped to `0..64` since the fingerprint
+/// itself is a u64.
+fn fastcdc_mask(bits: u32) -> u64 {
+    let bits = bits.min(63);
+    (1u64 << bits) - 1
+}
+
+/// Finds the next FastCDC cut point in `data`, i.e. the length of the next
+/// chunk to emit.
+///
+/// This is "normalized chunking": while the chunk is smaller than `avg` we
+/// test against a stricter mask (more required zero bits, so a cut is less
+/// likely), and once it reaches `avg` we switch to a looser mask (fewer
+/// required zero bits, so a cut becomes more likely). This keeps the chunk
+/// size distribution tightly clustered around `avg` instead of the wide
+/// spread a single fixed mask produces.
+///
+/// Never returns a cut before `min`, and always cuts by `max` (or at
+/// `data.len()` if the data runs out first). Returns `0` only if `data` is
+/// empty.
+pub fn fastcdc_next_cut(data: &[u8], min: usize, avg: usize, max: usize) -> usize {
+    let len = data.len();
+    if len <= min {
+        return len;
+    }
+
+    let avg_bits = (avg.max(1) as f64).log2().round() as u32;
+    let mask_s = fastcdc_mask(avg_bits + 2);
+    let mask_l = fastcdc_mask(avg_bits.saturating_sub(2));
+    let max_cut = max.min(len);
+
+    let mut fp: u64 = 0;
+    let mut i = 0;
+
+    // Feed the fingerprint for the bytes we're not allowed to cut at yet.
+    while i < min {
+        fp = (fp << 1).wrapping_add(GEAR[data[i] as usize]);
+        i += 1;
+    }
+
+    while i < max_cut {
+        fp = (fp << 1).wrapping_add(GEAR[data[i] as usize]);
+        let mask = if i < avg { mask_s } else { mask_l };
+        i += 1;
+        if fp & mask == 0 {
+            return i;
+        }
+    }
+
+    max_cut
+}
+
+/// Per-chunk compression algorithm, chosen once for a whole stream via
+/// [`StreamConfig`] but recorded individually per chunk in its
+/// [`ChunkRecord`] — following Proxmox's `DataBlob` design, where each
+/// blob's header self-describes whether it's compressed rather than relying
+/// on a file-wide setting. This lets the encryptor fall back to storing any
+/// one chunk raw (tagged [`Compression::None`]) whenever compressing it
+/// didn't actually save space, without affecting the rest of the stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Store chunks as-is
+    None,
+    /// Zstandard at the given level (1-22; higher compresses more, slower)
+    Zstd { level: i32 },
+    /// LZ4, favoring speed over ratio
+    Lz4,
+}
+
+impl Compression {
+    /// One-byte tag identifying the algorithm a chunk was actually stored
+    /// with, as recorded in its [`ChunkRecord`]. `0` always means "raw",
+    /// regardless of what [`Compression`] the stream is configured with,
+    /// since any chunk can fall back to raw storage independently.
+    fn tag(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd { .. } => 1,
+            Compression::Lz4 => 2,
+        }
+    }
+
+    /// Compresses `plaintext`, but only if doing so actually makes it
+    /// smaller. Returns `None` (meaning: store `plaintext` raw) when
+    /// compression is disabled or failed to shrink the chunk, so
+    /// incompressible chunks never grow from being run through a
+    /// compressor anyway.
+    fn compress(self, plaintext: &[u8]) -> Option<Vec<u8>> {
+        let compressed = match self {
+            Compression::None => return None,
+            Compression::Zstd { level } => zstd::stream::encode_all(plaintext, level).ok()?,
+            Compression::Lz4 => lz4_flex::compress_prepend_size(plaintext),
+        };
+
+        if compressed.len() < plaintext.len() {
+            Some(compressed)
+        } else {
+            None
+        }
+    }
+
+    /// Reverses [`Compression::compress`] given the one-byte tag a chunk was
+    /// stored with.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CryptorError::InvalidFormat`] for an unrecognized tag, or
+    /// [`CryptorError::Cryptography`] if decompression itself fails.
+    fn decompress(tag: u8, data: &[u8]) -> Result<Vec<u8>> {
+        match tag {
+            0 => Ok(data.to_vec()),
+            1 => zstd::stream::decode_all(data)
+                .map_err(|e| CryptorError::Cryptography(format!("zstd decompression failed: {}", e))),
+            2 => lz4_flex::decompress_size_prepended(data)
+                .map_err(|e| CryptorError::Cryptography(format!("lz4 decompression failed: {}", e))),
+            _ => Err(CryptorError::InvalidFormat),
+        }
+    }
+}
+
 /// Configuration for streaming encryption/decryption.
 #[derive(Debug, Clone, Copy)]
 pub struct StreamConfig {
-    /// Size of each chunk in bytes
-    pub chunk_size: usize,
-    /// Whether to enable compression before encryption
-    pub compress: bool,
+    /// How the plaintext stream is cut into chunks
+    pub chunking: ChunkingStrategy,
+    /// Compression to apply to each chunk before encryption. Chunks that
+    /// don't actually shrink are stored raw regardless of this setting (see
+    /// [`Compression::compress`]).
+    pub compression: Compression,
+    /// Whether chunks are convergently encrypted (see
+    /// [`crate::crypto::convergent`]) instead of under this stream's random
+    /// base nonce. Opt-in and off by default: convergent encryption lets
+    /// identical chunks across files dedupe, but it also lets anyone who
+    /// already has a candidate plaintext confirm whether it's present in
+    /// the store (a confirmation-of-file attack), which the default
+    /// random-nonce scheme does not allow. Convergent chunks are always
+    /// stored uncompressed: `compression` is not applied to them.
+    pub convergent: bool,
 }
 
 impl Default for StreamConfig {
     fn default() -> Self {
         Self {
-            chunk_size: DEFAULT_CHUNK_SIZE,
-            compress: false,
+            chunking: ChunkingStrategy::Fixed(DEFAULT_CHUNK_SIZE),
+            compression: Compression::None,
+            convergent: false,
         }
     }
 }
 
 impl StreamConfig {
-    /// Creates a new stream configuration with custom chunk size.
+    /// Creates a new stream configuration with a fixed chunk size.
     ///
     /// # Arguments
     ///
@@ -96,30 +387,64 @@ impl StreamConfig {
         }
 
         Ok(Self {
-            chunk_size,
-            compress: false,
+            chunking: ChunkingStrategy::Fixed(chunk_size),
+            compression: Compression::None,
+            convergent: false,
         })
     }
 
-    /// Enables or disables compression.
-    pub fn with_compression(mut self, compress: bool) -> Self {
-        self.compress = compress;
+    /// Creates a new stream configuration using FastCDC content-defined
+    /// chunking, for streams that benefit from deduplication.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless `min <= avg <= max`.
+    pub fn new_fastcdc(min: usize, avg: usize, max: usize) -> Result<Self> {
+        if !(min <= avg && avg <= max) {
+            return Err(CryptorError::Cryptography(format!(
+                "FastCDC bounds must satisfy min <= avg <= max, got min={}, avg={}, max={}",
+                min, avg, max
+            )));
+        }
+
+        Ok(Self {
+            chunking: ChunkingStrategy::FastCdc { min, avg, max },
+            compression: Compression::None,
+            convergent: false,
+        })
+    }
+
+    /// Sets the per-chunk compression algorithm. See [`Compression`] for how
+    /// individual chunks fall back to raw storage when compression doesn't
+    /// help.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Enables or disables convergent (content-addressed) chunk encryption.
+    /// See [`StreamConfig::convergent`] for the privacy trade-off this opts
+    /// into.
+    pub fn with_convergent(mut self, convergent: bool) -> Self {
+        self.convergent = convergent;
         self
     }
 
     /// Creates a configuration optimized for fast processing.
     pub fn fast() -> Self {
         Self {
-            chunk_size: 4 * 1024 * 1024, // 4 MB chunks
-            compress: false,
+            chunking: ChunkingStrategy::Fixed(4 * 1024 * 1024), // 4 MB chunks
+            compression: Compression::None,
+            convergent: false,
         }
     }
 
     /// Creates a configuration optimized for small memory footprint.
     pub fn low_memory() -> Self {
         Self {
-            chunk_size: 64 * 1024, // 64 KB chunks
-            compress: true,
+            chunking: ChunkingStrategy::Fixed(64 * 1024), // 64 KB chunks
+            compression: Compression::Zstd { level: 3 },
+            convergent: false,
         }
     }
 }
@@ -168,18 +493,25 @@ pub struct StreamHeader {
     pub salt: String,
     /// Base nonce for deriving chunk nonces
     pub base_nonce: [u8; NONCE_LEN],
-    /// Size of each chunk in bytes
-    pub chunk_size: u32,
+    /// How the plaintext was cut into chunks
+    pub chunking: ChunkingStrategy,
     /// Total number of chunks in the file
     pub total_chunks: u64,
     /// Original file size in bytes
     pub original_size: u64,
     /// Optional metadata (JSON)
     pub metadata: Option<String>,
+    /// Offset index for random-access decryption (see [`SeekableDecryptor`]).
+    /// Empty if the file was written without one, in which case chunks must
+    /// be read sequentially from the start.
+    pub chunk_index: Vec<ChunkIndexEntry>,
 }
 
 impl StreamHeader {
-    /// Calculates the number of chunks needed for a given file size.
+    /// Calculates the number of chunks needed for a given file size under
+    /// [`ChunkingStrategy::Fixed`]. There is no closed-form equivalent for
+    /// [`ChunkingStrategy::FastCdc`], since chunk boundaries depend on the
+    /// plaintext itself; callers on that path must count chunks as they go.
     pub fn calculate_chunks(file_size: u64, chunk_size: u32) -> u64 {
         let chunk_size = chunk_size as u64;
         (file_size + chunk_size - 1) / chunk_size
@@ -206,8 +538,18 @@ impl StreamHeader {
         // Base nonce
         writer.write_all(&self.base_nonce)?;
 
-        // Chunk size
-        writer.write_all(&self.chunk_size.to_le_bytes())?;
+        // Chunking strategy
+        writer.write_all(&[self.chunking.tag()])?;
+        match self.chunking {
+            ChunkingStrategy::Fixed(size) => {
+                writer.write_all(&(size as u32).to_le_bytes())?;
+            }
+            ChunkingStrategy::FastCdc { min, avg, max } => {
+                writer.write_all(&(min as u32).to_le_bytes())?;
+                writer.write_all(&(avg as u32).to_le_bytes())?;
+                writer.write_all(&(max as u32).to_le_bytes())?;
+            }
+        }
 
         // Total chunks
         writer.write_all(&self.total_chunks.to_le_bytes())?;
@@ -221,6 +563,15 @@ impl StreamHeader {
         writer.write_all(&metadata_len.to_le_bytes())?;
         writer.write_all(metadata_bytes)?;
 
+        // Chunk offset index
+        writer.write_all(&(self.chunk_index.len() as u32).to_le_bytes())?;
+        for entry in &self.chunk_index {
+            writer.write_all(&entry.chunk_index.to_le_bytes())?;
+            writer.write_all(&entry.file_offset.to_le_bytes())?;
+            writer.write_all(&entry.stored_len.to_le_bytes())?;
+            writer.write_all(&entry.plain_len.to_le_bytes())?;
+        }
+
         Ok(())
     }
 
@@ -258,10 +609,30 @@ impl StreamHeader {
         let mut base_nonce = [0u8; NONCE_LEN];
         reader.read_exact(&mut base_nonce)?;
 
-        // Chunk size
-        let mut chunk_size_bytes = [0u8; 4];
-        reader.read_exact(&mut chunk_size_bytes)?;
-        let chunk_size = u32::from_le_bytes(chunk_size_bytes);
+        // Chunking strategy
+        let mut tag = [0u8; 1];
+        reader.read_exact(&mut tag)?;
+        let chunking = match tag[0] {
+            0 => {
+                let mut size_bytes = [0u8; 4];
+                reader.read_exact(&mut size_bytes)?;
+                ChunkingStrategy::Fixed(u32::from_le_bytes(size_bytes) as usize)
+            }
+            1 => {
+                let mut min_bytes = [0u8; 4];
+                let mut avg_bytes = [0u8; 4];
+                let mut max_bytes = [0u8; 4];
+                reader.read_exact(&mut min_bytes)?;
+                reader.read_exact(&mut avg_bytes)?;
+                reader.read_exact(&mut max_bytes)?;
+                ChunkingStrategy::FastCdc {
+                    min: u32::from_le_bytes(min_bytes) as usize,
+                    avg: u32::from_le_bytes(avg_bytes) as usize,
+                    max: u32::from_le_bytes(max_bytes) as usize,
+                }
+            }
+            _ => return Err(CryptorError::InvalidFormat),
+        };
 
         // Total chunks
         let mut total_chunks_bytes = [0u8; 8];
@@ -287,15 +658,773 @@ impl StreamHeader {
             None
         };
 
+        // Chunk offset index
+        let mut index_count_bytes = [0u8; 4];
+        reader.read_exact(&mut index_count_bytes)?;
+        let index_count = u32::from_le_bytes(index_count_bytes) as usize;
+
+        let mut chunk_index = Vec::with_capacity(index_count);
+        for _ in 0..index_count {
+            let mut chunk_index_bytes = [0u8; 8];
+            reader.read_exact(&mut chunk_index_bytes)?;
+            let mut file_offset_bytes = [0u8; 8];
+            reader.read_exact(&mut file_offset_bytes)?;
+            let mut stored_len_bytes = [0u8; 4];
+            reader.read_exact(&mut stored_len_bytes)?;
+            let mut plain_len_bytes = [0u8; 4];
+            reader.read_exact(&mut plain_len_bytes)?;
+
+            chunk_index.push(ChunkIndexEntry {
+                chunk_index: u64::from_le_bytes(chunk_index_bytes),
+                file_offset: u64::from_le_bytes(file_offset_bytes),
+                stored_len: u32::from_le_bytes(stored_len_bytes),
+                plain_len: u32::from_le_bytes(plain_len_bytes),
+            });
+        }
+
         Ok(Self {
             salt,
             base_nonce,
-            chunk_size,
+            chunking,
             total_chunks,
             original_size,
             metadata,
+            chunk_index,
+        })
+    }
+}
+
+/// 8-byte magic tag written ahead of every chunk record in the V2 streaming
+/// format, so [`StreamHeader::verify_integrity`] can recognize a chunk
+/// boundary and scan for corruption without needing the file's key.
+pub const CHUNK_MAGIC: &[u8; 8] = b"TSXCHUNK";
+
+/// Fixed-size overhead a [`ChunkRecord`] adds around its ciphertext: magic
+/// (8) + CRC-32 (4) + chunk index (8) + compression tag (1) + ciphertext
+/// length (4).
+const CHUNK_RECORD_OVERHEAD: u64 = CHUNK_MAGIC.len() as u64 + 4 + 8 + 1 + 4;
+
+/// CRC-32 (IEEE 802.3, reflected, poly 0xEDB88320) lookup table.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut byte = 0usize;
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+};
+
+/// Computes the CRC-32 (IEEE 802.3) checksum of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+    for &byte in data {
+        let index = ((crc ^ byte as u32) & 0xFF) as usize;
+        crc = (crc >> 8) ^ CRC32_TABLE[index];
+    }
+    !crc
+}
+
+/// One chunk record in the V2 streaming format: a magic tag and CRC-32 of
+/// the stored ciphertext (checkable without the file's key), followed by
+/// the chunk index, the algorithm its plaintext was compressed with (if
+/// any), its length, and the AEAD-sealed ciphertext itself.
+///
+/// ```text
+/// Magic: 8 bytes ("TSXCHUNK")
+/// CRC-32 of ciphertext: u32 (4 bytes)
+/// Chunk index: u64 (8 bytes)
+/// Compression tag: u8 (1 byte; 0 = raw, 1 = Zstd, 2 = Lz4)
+/// Ciphertext length: u32 (4 bytes)
+/// Ciphertext (+ AEAD tag)
+/// ```
+///
+/// The compression tag describes what the *sealed plaintext* was before
+/// encryption, so the decryptor knows whether to inflate it after opening
+/// the chunk. It's recorded per chunk rather than once for the whole
+/// stream because a chunk that didn't actually shrink under compression is
+/// stored raw regardless of the stream's configured [`Compression`] (see
+/// [`Compression::compress`]).
+#[derive(Debug, Clone)]
+pub struct ChunkRecord {
+    /// Index of this chunk within the stream
+    pub index: u64,
+    /// One-byte tag (see [`Compression::tag`]) identifying how this
+    /// chunk's plaintext was compressed, if at all, before encryption
+    pub compression_tag: u8,
+    /// AEAD-sealed chunk data (ciphertext + tag)
+    pub ciphertext: Vec<u8>,
+}
+
+impl ChunkRecord {
+    /// Writes this chunk record, including its magic tag and CRC-32.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(CHUNK_MAGIC)?;
+        writer.write_all(&crc32(&self.ciphertext).to_le_bytes())?;
+        writer.write_all(&self.index.to_le_bytes())?;
+        writer.write_all(&[self.compression_tag])?;
+        writer.write_all(&(self.ciphertext.len() as u32).to_le_bytes())?;
+        writer.write_all(&self.ciphertext)?;
+        Ok(())
+    }
+
+    /// Reads one chunk record, verifying its magic tag and CRC-32 but
+    /// without attempting AEAD decryption.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CryptorError::InvalidFormat`] if the magic tag doesn't
+    /// match, or a CRC mismatch is detected.
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != CHUNK_MAGIC {
+            return Err(CryptorError::InvalidFormat);
+        }
+
+        let mut crc_bytes = [0u8; 4];
+        reader.read_exact(&mut crc_bytes)?;
+        let expected_crc = u32::from_le_bytes(crc_bytes);
+
+        let mut index_bytes = [0u8; 8];
+        reader.read_exact(&mut index_bytes)?;
+        let index = u64::from_le_bytes(index_bytes);
+
+        let mut compression_tag = [0u8; 1];
+        reader.read_exact(&mut compression_tag)?;
+        let compression_tag = compression_tag[0];
+
+        let mut len_bytes = [0u8; 4];
+        reader.read_exact(&mut len_bytes)?;
+        let len = u32::from_le_bytes(len_bytes) as usize;
+
+        let mut ciphertext = vec![0u8; len];
+        reader.read_exact(&mut ciphertext)?;
+
+        if crc32(&ciphertext) != expected_crc {
+            return Err(CryptorError::InvalidFormat);
+        }
+
+        Ok(Self { index, compression_tag, ciphertext })
+    }
+}
+
+/// First chunk found to be corrupt by [`StreamHeader::verify_integrity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorruptChunk {
+    /// 0-based position of the corrupt chunk among the records scanned
+    /// (its own recorded index is not trusted, since the record is corrupt)
+    pub index: u64,
+    /// Byte offset of the corrupt chunk's record within the stream,
+    /// relative to wherever the reader started (typically right after the
+    /// [`StreamHeader`])
+    pub byte_offset: u64,
+}
+
+impl StreamHeader {
+    /// Walks `total_chunks` chunk records from `reader`, checking each
+    /// magic tag and CRC-32 without performing any AEAD decryption.
+    ///
+    /// This is a cheap "is this archive intact?" scan: it catches bitrot,
+    /// truncation, and corrupted records, but — since it never touches the
+    /// key — it cannot detect ciphertext that was tampered with in a way
+    /// that preserves the CRC. Use full decryption for that guarantee.
+    ///
+    /// Returns the first corrupt chunk found, if any, along with its byte
+    /// offset so a caller can skip past it for partial recovery.
+    pub fn verify_integrity<R: Read>(reader: &mut R, total_chunks: u64) -> Result<Option<CorruptChunk>> {
+        let mut offset = 0u64;
+
+        for expected_index in 0..total_chunks {
+            match ChunkRecord::read_from(reader) {
+                Ok(record) => {
+                    offset += CHUNK_RECORD_OVERHEAD + record.ciphertext.len() as u64;
+                }
+                Err(_) => {
+                    return Ok(Some(CorruptChunk {
+                        index: expected_index,
+                        byte_offset: offset,
+                    }));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Length in bytes of the big-endian chunk counter appended to the base
+/// nonce for the per-chunk AEAD streaming cipher (see [`StreamEncryptor`])
+pub const STREAM_COUNTER_LEN: usize = 4;
+
+/// Default chunk size for [`StreamEncryptor`]/[`StreamDecryptor`]: 64 KiB.
+///
+/// Unlike [`DEFAULT_CHUNK_SIZE`] (used by the buffered file-format-v2 path),
+/// this is sized for encrypting directly in a browser via WASM, where
+/// holding multiple large buffers in memory at once is expensive.
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Seals one stream chunk under `cipher`/`key`/`nonce`, binding `aad`.
+pub(crate) fn seal_chunk(cipher: CipherAlgorithm, key: &[u8], nonce: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let result = match cipher {
+        CipherAlgorithm::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid AES-256-GCM key: {}", e)))?
+            .encrypt(nonce.into(), AesGcmPayload { msg: plaintext, aad }),
+        CipherAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid ChaCha20 key: {}", e)))?
+            .encrypt(nonce.into(), ChaChaPayload { msg: plaintext, aad }),
+        CipherAlgorithm::XChaCha20Poly1305 => XChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid XChaCha20 key: {}", e)))?
+            .encrypt(nonce.into(), ChaChaPayload { msg: plaintext, aad }),
+    };
+    result.map_err(|e| CryptorError::Cryptography(format!("chunk encryption failed: {}", e)))
+}
+
+/// Opens one stream chunk sealed by [`seal_chunk`]. Fails if `nonce` or `aad`
+/// do not match what the chunk was sealed with, which is how reordered,
+/// duplicated, truncated, or otherwise tampered chunks are rejected.
+pub(crate) fn open_chunk(cipher: CipherAlgorithm, key: &[u8], nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let result = match cipher {
+        CipherAlgorithm::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid AES-256-GCM key: {}", e)))?
+            .decrypt(nonce.into(), AesGcmPayload { msg: ciphertext, aad }),
+        CipherAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid ChaCha20 key: {}", e)))?
+            .decrypt(nonce.into(), ChaChaPayload { msg: ciphertext, aad }),
+        CipherAlgorithm::XChaCha20Poly1305 => XChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptorError::Cryptography(format!("Invalid XChaCha20 key: {}", e)))?
+            .decrypt(nonce.into(), ChaChaPayload { msg: ciphertext, aad }),
+    };
+    result.map_err(|_| CryptorError::Cryptography("chunk authentication failed".to_string()))
+}
+
+/// Compresses `plaintext` per `compression` (falling back to raw storage
+/// when it doesn't shrink, see [`Compression::compress`]), then seals the
+/// result with [`seal_chunk`]. Returns the one-byte tag the chunk was
+/// actually stored with alongside the sealed ciphertext, ready to become a
+/// [`ChunkRecord`].
+pub(crate) fn seal_chunk_compressed(
+    cipher: CipherAlgorithm,
+    key: &[u8],
+    nonce: &[u8],
+    plaintext: &[u8],
+    aad: &[u8],
+    compression: Compression,
+) -> Result<(u8, Vec<u8>)> {
+    match compression.compress(plaintext) {
+        Some(compressed) => Ok((compression.tag(), seal_chunk(cipher, key, nonce, &compressed, aad)?)),
+        None => Ok((Compression::None.tag(), seal_chunk(cipher, key, nonce, plaintext, aad)?)),
+    }
+}
+
+/// Reverses [`seal_chunk_compressed`]: opens `ciphertext` with [`open_chunk`],
+/// then decompresses it per `compression_tag` (as recorded in the chunk's
+/// [`ChunkRecord`]).
+pub(crate) fn open_chunk_compressed(
+    cipher: CipherAlgorithm,
+    key: &[u8],
+    nonce: &[u8],
+    ciphertext: &[u8],
+    aad: &[u8],
+    compression_tag: u8,
+) -> Result<Vec<u8>> {
+    let plaintext = open_chunk(cipher, key, nonce, ciphertext, aad)?;
+    Compression::decompress(compression_tag, &plaintext)
+}
+
+/// Builds the nonce for `chunk_index`: `base_nonce || chunk_index` (big-endian).
+fn stream_chunk_nonce(base_nonce: &[u8], chunk_index: u32) -> Vec<u8> {
+    let mut nonce = base_nonce.to_vec();
+    nonce.extend_from_slice(&chunk_index.to_be_bytes());
+    nonce
+}
+
+/// Builds the associated data for `chunk_index`: the big-endian counter
+/// followed by a one-byte flag that is `1` for the final chunk of the
+/// stream and `0` otherwise. Binding the counter prevents chunk reordering
+/// and duplication; binding the final flag prevents an attacker from
+/// silently dropping trailing chunks and passing off an earlier chunk as
+/// the end of the stream.
+fn stream_chunk_aad(chunk_index: u32, is_final: bool) -> [u8; STREAM_COUNTER_LEN + 1] {
+    let mut aad = [0u8; STREAM_COUNTER_LEN + 1];
+    aad[..STREAM_COUNTER_LEN].copy_from_slice(&chunk_index.to_be_bytes());
+    aad[STREAM_COUNTER_LEN] = is_final as u8;
+    aad
+}
+
+/// Encrypts a plaintext stream one fixed-size chunk at a time, sealing each
+/// chunk independently so the ciphertext never has to be buffered in full.
+///
+/// Each chunk's nonce is `base_nonce || chunk_index` (big-endian), and the
+/// chunk index plus an end-of-stream flag are bound into the AEAD
+/// associated data, so chunks cannot be reordered, duplicated, or silently
+/// dropped from the end of the stream without the next decryption failing.
+/// The base nonce is generated once per stream and must be emitted ahead of
+/// the sealed chunks (e.g. in a [`StreamHeader`]) so the decryptor can
+/// reconstruct each chunk's nonce.
+pub struct StreamEncryptor {
+    cipher: CipherAlgorithm,
+    key: Zeroizing<Vec<u8>>,
+    base_nonce: Vec<u8>,
+    chunk_index: u32,
+    finished: bool,
+}
+
+impl StreamEncryptor {
+    /// Creates a new stream encryptor.
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher` - AEAD algorithm to seal chunks with
+    /// * `key` - Derived volume/file key; wiped from memory once the
+    ///   encryptor (or its caller's copy) is dropped
+    /// * `base_nonce` - Randomly generated once per stream; must be exactly
+    ///   `cipher.nonce_len() - STREAM_COUNTER_LEN` bytes long
+    pub fn new(cipher: CipherAlgorithm, key: impl Into<Zeroizing<Vec<u8>>>, base_nonce: Vec<u8>) -> Result<Self> {
+        if base_nonce.len() != cipher.nonce_len() - STREAM_COUNTER_LEN {
+            return Err(CryptorError::Cryptography(format!(
+                "base nonce must be {} bytes for this cipher, got {}",
+                cipher.nonce_len() - STREAM_COUNTER_LEN,
+                base_nonce.len()
+            )));
+        }
+
+        Ok(Self {
+            cipher,
+            key: key.into(),
+            base_nonce,
+            chunk_index: 0,
+            finished: false,
+        })
+    }
+
+    /// Returns the base nonce, to be emitted once in the stream header
+    pub fn base_nonce(&self) -> &[u8] {
+        &self.base_nonce
+    }
+
+    /// Seals the next chunk of plaintext, which is not the last chunk of
+    /// the stream. Returns `ciphertext || 16-byte tag`.
+    pub fn encrypt_chunk(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        self.seal(plaintext, false)
+    }
+
+    /// Seals the final chunk of the stream, consuming the encryptor so no
+    /// further chunks can be sealed afterwards. Returns `ciphertext ||
+    /// 16-byte tag`.
+    pub fn finish(mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        self.seal(plaintext, true)
+    }
+
+    fn seal(&mut self, plaintext: &[u8], is_final: bool) -> Result<Vec<u8>> {
+        if self.finished {
+            return Err(CryptorError::Cryptography("stream has already been finished".to_string()));
+        }
+
+        let nonce = stream_chunk_nonce(&self.base_nonce, self.chunk_index);
+        let aad = stream_chunk_aad(self.chunk_index, is_final);
+        let sealed = seal_chunk(self.cipher, &self.key, &nonce, plaintext, &aad)?;
+
+        self.chunk_index += 1;
+        self.finished = is_final;
+
+        Ok(sealed)
+    }
+}
+
+/// Decrypts a stream sealed by [`StreamEncryptor`], verifying each chunk as
+/// it is consumed.
+///
+/// Chunks must be fed in the exact order they were produced: the decryptor
+/// tracks its own expected chunk index and reconstructs the nonce/AAD from
+/// it, so a reordered, duplicated, or truncated chunk fails authentication
+/// rather than silently decrypting to the wrong plaintext.
+pub struct StreamDecryptor {
+    cipher: CipherAlgorithm,
+    key: Zeroizing<Vec<u8>>,
+    base_nonce: Vec<u8>,
+    chunk_index: u32,
+    finished: bool,
+}
+
+impl StreamDecryptor {
+    /// Creates a new stream decryptor for the base nonce emitted by the
+    /// matching [`StreamEncryptor`].
+    pub fn new(cipher: CipherAlgorithm, key: impl Into<Zeroizing<Vec<u8>>>, base_nonce: Vec<u8>) -> Result<Self> {
+        if base_nonce.len() != cipher.nonce_len() - STREAM_COUNTER_LEN {
+            return Err(CryptorError::Cryptography(format!(
+                "base nonce must be {} bytes for this cipher, got {}",
+                cipher.nonce_len() - STREAM_COUNTER_LEN,
+                base_nonce.len()
+            )));
+        }
+
+        Ok(Self {
+            cipher,
+            key: key.into(),
+            base_nonce,
+            chunk_index: 0,
+            finished: false,
+        })
+    }
+
+    /// Opens the next non-final chunk of the stream
+    pub fn decrypt_chunk(&mut self, sealed: &[u8]) -> Result<Vec<u8>> {
+        self.open(sealed, false)
+    }
+
+    /// Opens the final chunk of the stream, consuming the decryptor so no
+    /// further chunks can be accepted afterwards. Fails if `sealed` was not
+    /// actually sealed as the stream's final chunk, which is how a
+    /// truncated stream (missing its true final chunk) is detected.
+    pub fn finish(mut self, sealed: &[u8]) -> Result<Vec<u8>> {
+        self.open(sealed, true)
+    }
+
+    fn open(&mut self, sealed: &[u8], is_final: bool) -> Result<Vec<u8>> {
+        if self.finished {
+            return Err(CryptorError::Cryptography("stream has already been finished".to_string()));
+        }
+
+        let nonce = stream_chunk_nonce(&self.base_nonce, self.chunk_index);
+        let aad = stream_chunk_aad(self.chunk_index, is_final);
+        let plaintext = open_chunk(self.cipher, &self.key, &nonce, sealed, &aad)?;
+
+        self.chunk_index += 1;
+        self.finished = is_final;
+
+        Ok(plaintext)
+    }
+}
+
+/// One entry in a [`StreamHeader`]'s chunk offset index: records where a
+/// given chunk's [`ChunkRecord`] begins, so it can be located and decrypted
+/// without scanning the chunks before it. Analogous to Proxmox's
+/// `ChunkInfo { offset, chunk_len }` in its `.fidx`/`.didx` index files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkIndexEntry {
+    /// Index of the chunk this entry describes
+    pub chunk_index: u64,
+    /// Byte offset of the chunk's [`ChunkRecord`], relative to the start of
+    /// the chunk data section (i.e. right after the header)
+    pub file_offset: u64,
+    /// Length in bytes of the chunk's stored ciphertext (AEAD tag included)
+    pub stored_len: u32,
+    /// Length in bytes of the chunk's *plaintext* (i.e. after decompression).
+    /// Needed alongside `stored_len` because a compressed chunk's plaintext
+    /// length can't be derived from its ciphertext length, unlike the
+    /// uncompressed case (ciphertext length minus the AEAD tag).
+    pub plain_len: u32,
+}
+
+/// Builds the associated data for a chunk addressed by [`SeekableDecryptor`]:
+/// just the big-endian chunk index, which prevents a chunk fetched for one
+/// index from being passed off as another.
+fn header_chunk_aad(chunk_index: u64) -> [u8; 8] {
+    chunk_index.to_be_bytes()
+}
+
+/// Checks that `cipher` is compatible with [`derive_chunk_nonce`]'s fixed
+/// `NONCE_LEN`-byte (12) base nonce, as used by [`IndexedStreamWriter`] and
+/// [`SeekableDecryptor`]. Unlike [`StreamEncryptor`], which sizes its base
+/// nonce to `cipher.nonce_len() - STREAM_COUNTER_LEN` and so supports every
+/// cipher, the indexed V2 format's nonce derivation is hardcoded to 12
+/// bytes; a 24-byte-nonce cipher like XChaCha20-Poly1305 would otherwise
+/// panic deep inside `seal_chunk`/`open_chunk` rather than failing cleanly.
+fn require_fixed_nonce_cipher(cipher: CipherAlgorithm) -> Result<()> {
+    if cipher.nonce_len() != NONCE_LEN {
+        return Err(CryptorError::Cryptography(format!(
+            "{:?} uses a {}-byte nonce, but the indexed stream format only supports {}-byte nonce ciphers",
+            cipher,
+            cipher.nonce_len(),
+            NONCE_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Decrypts individual chunks of a V2-format stream at arbitrary byte
+/// offsets, using a [`StreamHeader`]'s [`ChunkIndexEntry`] table to seek
+/// directly to the chunk(s) covering a requested range instead of
+/// decrypting everything before them.
+///
+/// This is what lets something like a FUSE-mounted volume read the middle
+/// of a multi-gigabyte encrypted file without touching the rest of it.
+/// Requires `derive_chunk_nonce`'s per-chunk-index nonce derivation, which
+/// only depends on the chunk's own index — unlike [`StreamDecryptor`], no
+/// earlier chunk needs to be processed first.
+pub struct SeekableDecryptor<R> {
+    reader: R,
+    cipher: CipherAlgorithm,
+    key: Zeroizing<Vec<u8>>,
+    base_nonce: [u8; NONCE_LEN],
+    /// Offset, relative to `reader`, of the first byte of the chunk data
+    /// section (i.e. right after the header)
+    chunk_data_start: u64,
+    chunk_index: Vec<ChunkIndexEntry>,
+    /// Plaintext byte offset of each chunk's first byte in the original
+    /// file, parallel to `chunk_index`
+    chunk_plain_offsets: Vec<u64>,
+}
+
+impl<R: Read + std::io::Seek> SeekableDecryptor<R> {
+    /// Creates a new seekable decryptor over `reader`, whose stream position
+    /// does not need to be anywhere in particular — every read seeks first.
+    ///
+    /// `chunk_index` should be the table from the matching [`StreamHeader`];
+    /// it must be sorted by `chunk_index.chunk_index` ascending and cover
+    /// the file contiguously, i.e. as produced by a single streaming
+    /// encryption pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CryptorError::Cryptography`] if `cipher` doesn't use a
+    /// `NONCE_LEN`-byte nonce (see [`require_fixed_nonce_cipher`]).
+    pub fn new(
+        reader: R,
+        cipher: CipherAlgorithm,
+        key: impl Into<Zeroizing<Vec<u8>>>,
+        base_nonce: [u8; NONCE_LEN],
+        chunk_data_start: u64,
+        chunk_index: Vec<ChunkIndexEntry>,
+    ) -> Result<Self> {
+        require_fixed_nonce_cipher(cipher)?;
+
+        let mut chunk_plain_offsets = Vec::with_capacity(chunk_index.len());
+        let mut cursor = 0u64;
+        for entry in &chunk_index {
+            chunk_plain_offsets.push(cursor);
+            cursor += entry.plain_len as u64;
+        }
+
+        Ok(Self {
+            reader,
+            cipher,
+            key: key.into(),
+            base_nonce,
+            chunk_data_start,
+            chunk_index,
+            chunk_plain_offsets,
+        })
+    }
+
+    /// Indices into `self.chunk_index` of every chunk overlapping the
+    /// plaintext byte range `start..end`.
+    fn covering_chunks(&self, start: u64, end: u64) -> Vec<usize> {
+        let mut covering = Vec::new();
+        for (i, entry) in self.chunk_index.iter().enumerate() {
+            let chunk_start = self.chunk_plain_offsets[i];
+            if chunk_start >= end {
+                break;
+            }
+            let chunk_end = chunk_start + entry.plain_len as u64;
+            if chunk_end > start {
+                covering.push(i);
+            }
+        }
+        covering
+    }
+
+    /// Decrypts only the chunk(s) covering the plaintext byte range
+    /// `start..end` of the original file, returning exactly those bytes.
+    ///
+    /// Seeks directly to each covering chunk's recorded offset rather than
+    /// reading sequentially from the start of the file.
+    pub fn read_range(&mut self, start: u64, end: u64) -> Result<Vec<u8>> {
+        if end < start {
+            return Err(CryptorError::Cryptography(
+                "range end precedes range start".to_string(),
+            ));
+        }
+
+        let covering = self.covering_chunks(start, end);
+        if covering.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let range_start = self.chunk_plain_offsets[covering[0]];
+        let mut plaintext = Vec::new();
+
+        for i in covering {
+            let entry = self.chunk_index[i];
+            self.reader
+                .seek(std::io::SeekFrom::Start(self.chunk_data_start + entry.file_offset))?;
+            let record = ChunkRecord::read_from(&mut self.reader)?;
+            if record.index != entry.chunk_index {
+                return Err(CryptorError::InvalidFormat);
+            }
+
+            let nonce = derive_chunk_nonce(&self.base_nonce, entry.chunk_index);
+            let aad = header_chunk_aad(entry.chunk_index);
+            let chunk_plaintext = open_chunk_compressed(
+                self.cipher,
+                &self.key,
+                &nonce,
+                &record.ciphertext,
+                &aad,
+                record.compression_tag,
+            )?;
+            plaintext.extend_from_slice(&chunk_plaintext);
+        }
+
+        let trim_start = (start - range_start) as usize;
+        let trim_end = ((end - range_start) as usize).min(plaintext.len());
+        Ok(plaintext[trim_start..trim_end].to_vec())
+    }
+}
+
+/// Encrypts a plaintext stream into the indexed V2 format [`SeekableDecryptor`]
+/// reads: each chunk is sealed with [`derive_chunk_nonce`]/[`header_chunk_aad`]
+/// (a per-chunk-index scheme, unlike [`StreamEncryptor`]'s running counter)
+/// and written out as a [`ChunkRecord`], while a [`ChunkIndexEntry`]
+/// accumulates for each one so the result can be handed straight to a
+/// [`StreamHeader`].
+///
+/// With [`StreamConfig::convergent`] set, chunks are instead sealed with
+/// [`encrypt_chunk_convergent`] and stored uncompressed; such output is not
+/// readable by [`SeekableDecryptor`], which only knows the scheme above.
+///
+/// Chunk boundaries are driven by [`StreamConfig::chunking`]: plaintext is
+/// buffered internally until enough has accumulated for
+/// [`ChunkingStrategy::next_chunk_len`] to cut a real boundary rather than
+/// one that's only short for lack of input so far. Call [`Self::finish`] once
+/// the whole plaintext has been fed in, to flush whatever remains as the
+/// final (possibly short) chunk.
+pub struct IndexedStreamWriter<W: Write> {
+    writer: W,
+    cipher: CipherAlgorithm,
+    key: Zeroizing<Vec<u8>>,
+    base_nonce: [u8; NONCE_LEN],
+    config: StreamConfig,
+    buffer: Vec<u8>,
+    next_index: u64,
+    bytes_written: u64,
+    plaintext_offset: u64,
+    chunk_index: Vec<ChunkIndexEntry>,
+    data_map: DataMap,
+}
+
+impl<W: Write> IndexedStreamWriter<W> {
+    /// Creates a new indexed stream writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination for the sealed chunk records
+    /// * `cipher` - AEAD algorithm to seal chunks with
+    /// * `key` - Derived volume/file key; wiped from memory once the writer
+    ///   (or its caller's copy) is dropped
+    /// * `base_nonce` - Randomly generated once per stream; must be emitted
+    ///   alongside the resulting chunk index (e.g. in a [`StreamHeader`]) so
+    ///   a decryptor can reconstruct each chunk's nonce
+    /// * `config` - Selects the chunking strategy and per-chunk compression
+    ///   algorithm (see [`StreamConfig::compression`])
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CryptorError::Cryptography`] if `cipher` doesn't use a
+    /// `NONCE_LEN`-byte nonce (see [`require_fixed_nonce_cipher`]).
+    pub fn new(
+        writer: W,
+        cipher: CipherAlgorithm,
+        key: impl Into<Zeroizing<Vec<u8>>>,
+        base_nonce: [u8; NONCE_LEN],
+        config: StreamConfig,
+    ) -> Result<Self> {
+        require_fixed_nonce_cipher(cipher)?;
+
+        Ok(Self {
+            writer,
+            cipher,
+            key: key.into(),
+            base_nonce,
+            config,
+            buffer: Vec::new(),
+            next_index: 0,
+            bytes_written: 0,
+            plaintext_offset: 0,
+            chunk_index: Vec::new(),
+            data_map: DataMap::default(),
         })
     }
+
+    /// Buffers `data`, sealing and writing out as many complete chunks as
+    /// the configured chunking strategy can now cut from the buffer.
+    pub fn write_plaintext(&mut self, data: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(data);
+        while self.buffer.len() >= self.config.chunking.min_lookahead() {
+            let cut = self.config.chunking.next_chunk_len(&self.buffer);
+            self.seal_and_write(cut)?;
+        }
+        Ok(())
+    }
+
+    /// Seals and writes whatever plaintext remains buffered as the final
+    /// (possibly short) chunk, then returns the chunk offset index, total
+    /// chunk count, and convergent [`DataMap`] for the matching
+    /// [`StreamHeader`].
+    ///
+    /// The returned [`DataMap`] is empty unless [`StreamConfig::convergent`]
+    /// was set; callers that don't use convergent encryption can ignore it.
+    ///
+    /// If nothing at all was written, still emits one empty final chunk, so
+    /// an empty plaintext round-trips through an empty-but-valid stream
+    /// rather than zero chunks.
+    pub fn finish(mut self) -> Result<(Vec<ChunkIndexEntry>, u64, DataMap)> {
+        if !self.buffer.is_empty() || self.next_index == 0 {
+            let len = self.buffer.len();
+            self.seal_and_write(len)?;
+        }
+        Ok((self.chunk_index, self.next_index, self.data_map))
+    }
+
+    fn seal_and_write(&mut self, len: usize) -> Result<()> {
+        let chunk: Vec<u8> = self.buffer.drain(..len).collect();
+        let index = self.next_index;
+
+        let (compression_tag, ciphertext) = if self.config.convergent {
+            let (content_hash, ciphertext) = encrypt_chunk_convergent(self.cipher, &self.key, &chunk)?;
+            self.data_map.entries.push(DataMapEntry {
+                content_hash,
+                offset: self.plaintext_offset,
+                length: chunk.len() as u32,
+            });
+            (Compression::None.tag(), ciphertext)
+        } else {
+            let nonce = derive_chunk_nonce(&self.base_nonce, index);
+            let aad = header_chunk_aad(index);
+            seal_chunk_compressed(self.cipher, &self.key, &nonce, &chunk, &aad, self.config.compression)?
+        };
+
+        let record = ChunkRecord {
+            index,
+            compression_tag,
+            ciphertext,
+        };
+        let file_offset = self.bytes_written;
+        record.write_to(&mut self.writer)?;
+
+        self.bytes_written += CHUNK_RECORD_OVERHEAD + record.ciphertext.len() as u64;
+        self.plaintext_offset += chunk.len() as u64;
+        self.chunk_index.push(ChunkIndexEntry {
+            chunk_index: index,
+            file_offset,
+            stored_len: record.ciphertext.len() as u32,
+            plain_len: chunk.len() as u32,
+        });
+        self.next_index += 1;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -348,10 +1477,11 @@ mod tests {
         let header = StreamHeader {
             salt: "test_salt_string".to_string(),
             base_nonce: [42u8; NONCE_LEN],
-            chunk_size: 1024 * 1024,
+            chunking: ChunkingStrategy::Fixed(1024 * 1024),
             total_chunks: 100,
             original_size: 100 * 1024 * 1024,
             metadata: Some("{\"compressed\":true}".to_string()),
+            chunk_index: Vec::new(),
         };
 
         let mut buffer = Vec::new();
@@ -362,20 +1492,721 @@ mod tests {
 
         assert_eq!(header.salt, decoded.salt);
         assert_eq!(header.base_nonce, decoded.base_nonce);
-        assert_eq!(header.chunk_size, decoded.chunk_size);
+        assert_eq!(header.chunking, decoded.chunking);
         assert_eq!(header.total_chunks, decoded.total_chunks);
         assert_eq!(header.original_size, decoded.original_size);
         assert_eq!(header.metadata, decoded.metadata);
+        assert_eq!(header.chunk_index, decoded.chunk_index);
+    }
+
+    #[test]
+    fn test_stream_header_roundtrip_fastcdc() {
+        let header = StreamHeader {
+            salt: "another_salt".to_string(),
+            base_nonce: [7u8; NONCE_LEN],
+            chunking: ChunkingStrategy::FastCdc {
+                min: 16 * 1024,
+                avg: 64 * 1024,
+                max: 256 * 1024,
+            },
+            total_chunks: 42,
+            original_size: 9 * 1024 * 1024,
+            metadata: None,
+            chunk_index: Vec::new(),
+        };
+
+        let mut buffer = Vec::new();
+        header.write_to(&mut buffer).unwrap();
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        let decoded = StreamHeader::read_from(&mut cursor).unwrap();
+
+        assert_eq!(header.chunking, decoded.chunking);
+        assert_eq!(header.total_chunks, decoded.total_chunks);
+    }
+
+    #[test]
+    fn test_stream_header_roundtrip_with_chunk_index() {
+        let header = StreamHeader {
+            salt: "indexed_salt".to_string(),
+            base_nonce: [9u8; NONCE_LEN],
+            chunking: ChunkingStrategy::Fixed(64 * 1024),
+            total_chunks: 3,
+            original_size: 176 * 1024,
+            metadata: None,
+            chunk_index: vec![
+                ChunkIndexEntry { chunk_index: 0, file_offset: 0, stored_len: 65_552, plain_len: 65_536 },
+                ChunkIndexEntry { chunk_index: 1, file_offset: 65_577, stored_len: 65_552, plain_len: 65_536 },
+                ChunkIndexEntry { chunk_index: 2, file_offset: 131_154, stored_len: 49_168, plain_len: 49_152 },
+            ],
+        };
+
+        let mut buffer = Vec::new();
+        header.write_to(&mut buffer).unwrap();
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        let decoded = StreamHeader::read_from(&mut cursor).unwrap();
+
+        assert_eq!(header.chunk_index, decoded.chunk_index);
     }
 
     #[test]
     fn test_config_presets() {
         let fast = StreamConfig::fast();
-        assert_eq!(fast.chunk_size, 4 * 1024 * 1024);
-        assert!(!fast.compress);
+        assert_eq!(fast.chunking, ChunkingStrategy::Fixed(4 * 1024 * 1024));
+        assert_eq!(fast.compression, Compression::None);
 
         let low_mem = StreamConfig::low_memory();
-        assert_eq!(low_mem.chunk_size, 64 * 1024);
-        assert!(low_mem.compress);
+        assert_eq!(low_mem.chunking, ChunkingStrategy::Fixed(64 * 1024));
+        assert_eq!(low_mem.compression, Compression::Zstd { level: 3 });
+    }
+
+    #[test]
+    fn test_compression_tags_are_stable() {
+        assert_eq!(Compression::None.tag(), 0);
+        assert_eq!(Compression::Zstd { level: 3 }.tag(), 1);
+        assert_eq!(Compression::Lz4.tag(), 2);
+    }
+
+    #[test]
+    fn test_compression_zstd_roundtrip() {
+        let plaintext = b"hello hello hello hello hello hello hello hello".repeat(10);
+        let compressed = Compression::Zstd { level: 3 }.compress(&plaintext).unwrap();
+        assert!(compressed.len() < plaintext.len());
+
+        let decompressed = Compression::decompress(Compression::Zstd { level: 3 }.tag(), &compressed).unwrap();
+        assert_eq!(decompressed, plaintext);
+    }
+
+    #[test]
+    fn test_compression_lz4_roundtrip() {
+        let plaintext = b"hello hello hello hello hello hello hello hello".repeat(10);
+        let compressed = Compression::Lz4.compress(&plaintext).unwrap();
+        assert!(compressed.len() < plaintext.len());
+
+        let decompressed = Compression::decompress(Compression::Lz4.tag(), &compressed).unwrap();
+        assert_eq!(decompressed, plaintext);
+    }
+
+    #[test]
+    fn test_compression_falls_back_to_raw_for_incompressible_data() {
+        // Pseudo-random bytes don't shrink under compression, so `compress`
+        // should report "store raw" rather than growing the chunk.
+        let data = pseudo_random_bytes(4096, 99);
+        assert_eq!(Compression::Zstd { level: 19 }.compress(&data), None);
+        assert_eq!(Compression::Lz4.compress(&data), None);
+    }
+
+    #[test]
+    fn test_compression_none_never_compresses() {
+        assert_eq!(Compression::None.compress(b"aaaaaaaaaaaaaaaaaaaa"), None);
+    }
+
+    #[test]
+    fn test_decompress_rejects_unknown_tag() {
+        assert!(Compression::decompress(99, b"whatever").is_err());
+    }
+
+    #[test]
+    fn test_seal_chunk_compressed_roundtrip() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let nonce = vec![2u8; 12];
+        let aad = b"chunk-aad";
+        let plaintext = b"hello hello hello hello hello hello hello hello".repeat(10);
+
+        let (tag, ciphertext) =
+            seal_chunk_compressed(cipher, &key, &nonce, &plaintext, aad, Compression::Zstd { level: 3 }).unwrap();
+        assert_eq!(tag, Compression::Zstd { level: 3 }.tag());
+
+        let decrypted = open_chunk_compressed(cipher, &key, &nonce, &ciphertext, aad, tag).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_seal_chunk_compressed_falls_back_to_raw_tag() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let nonce = vec![3u8; 12];
+        let aad = b"chunk-aad";
+        let plaintext = pseudo_random_bytes(256, 5);
+
+        let (tag, ciphertext) =
+            seal_chunk_compressed(cipher, &key, &nonce, &plaintext, aad, Compression::Zstd { level: 19 }).unwrap();
+        assert_eq!(tag, Compression::None.tag());
+
+        let decrypted = open_chunk_compressed(cipher, &key, &nonce, &ciphertext, aad, tag).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    fn test_key() -> Vec<u8> {
+        vec![7u8; 32]
+    }
+
+    #[test]
+    fn test_stream_encrypt_decrypt_roundtrip() {
+        let base_nonce = vec![1u8; 8];
+        let mut encryptor = StreamEncryptor::new(CipherAlgorithm::Aes256Gcm, test_key(), base_nonce.clone()).unwrap();
+        let mut decryptor = StreamDecryptor::new(CipherAlgorithm::Aes256Gcm, test_key(), base_nonce).unwrap();
+
+        let chunk0 = encryptor.encrypt_chunk(b"first chunk").unwrap();
+        let chunk1 = encryptor.encrypt_chunk(b"second chunk").unwrap();
+        let chunk2 = encryptor.finish(b"final chunk").unwrap();
+
+        assert_eq!(decryptor.decrypt_chunk(&chunk0).unwrap(), b"first chunk");
+        assert_eq!(decryptor.decrypt_chunk(&chunk1).unwrap(), b"second chunk");
+        assert_eq!(decryptor.finish(&chunk2).unwrap(), b"final chunk");
+    }
+
+    #[test]
+    fn test_stream_rejects_reordered_chunks() {
+        let base_nonce = vec![2u8; 8];
+        let mut encryptor = StreamEncryptor::new(CipherAlgorithm::Aes256Gcm, test_key(), base_nonce.clone()).unwrap();
+        let mut decryptor = StreamDecryptor::new(CipherAlgorithm::Aes256Gcm, test_key(), base_nonce).unwrap();
+
+        let chunk0 = encryptor.encrypt_chunk(b"first chunk").unwrap();
+        let chunk1 = encryptor.encrypt_chunk(b"second chunk").unwrap();
+
+        // Feed chunk1 where chunk0 was expected
+        assert!(decryptor.decrypt_chunk(&chunk1).is_err());
+        // The decryptor's expected index didn't advance, so the correct chunk still works
+        assert_eq!(decryptor.decrypt_chunk(&chunk0).unwrap(), b"first chunk");
+    }
+
+    #[test]
+    fn test_stream_rejects_truncated_final_chunk() {
+        let base_nonce = vec![3u8; 8];
+        let mut encryptor = StreamEncryptor::new(CipherAlgorithm::Aes256Gcm, test_key(), base_nonce.clone()).unwrap();
+        let mut decryptor = StreamDecryptor::new(CipherAlgorithm::Aes256Gcm, test_key(), base_nonce).unwrap();
+
+        let chunk0 = encryptor.encrypt_chunk(b"not the last chunk").unwrap();
+        decryptor.decrypt_chunk(&chunk0).unwrap();
+
+        // An attacker drops the true final chunk and tries to pass chunk0
+        // (already consumed) off as the end of the stream; even a fresh
+        // decryptor must reject it since it wasn't sealed with the final flag.
+        let mut decryptor = StreamDecryptor::new(CipherAlgorithm::Aes256Gcm, test_key(), base_nonce_for_test()).unwrap();
+        assert!(decryptor.finish(&chunk0).is_err());
+    }
+
+    fn base_nonce_for_test() -> Vec<u8> {
+        vec![3u8; 8]
+    }
+
+    #[test]
+    fn test_stream_rejects_wrong_cipher_nonce_len() {
+        assert!(StreamEncryptor::new(CipherAlgorithm::Aes256Gcm, test_key(), vec![0u8; 4]).is_err());
+        assert!(StreamEncryptor::new(CipherAlgorithm::XChaCha20Poly1305, test_key(), vec![0u8; 20]).is_ok());
+    }
+
+    #[test]
+    fn test_indexed_stream_writer_rejects_xchacha20() {
+        let config = StreamConfig::new_fastcdc(4 * 1024, 16 * 1024, 64 * 1024).unwrap();
+        let result = IndexedStreamWriter::new(
+            Vec::new(),
+            CipherAlgorithm::XChaCha20Poly1305,
+            test_key(),
+            [0u8; NONCE_LEN],
+            config,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_seekable_decryptor_rejects_xchacha20() {
+        let cursor = std::io::Cursor::new(Vec::<u8>::new());
+        let result = SeekableDecryptor::new(
+            cursor,
+            CipherAlgorithm::XChaCha20Poly1305,
+            test_key(),
+            [0u8; NONCE_LEN],
+            0,
+            Vec::new(),
+        );
+        assert!(result.is_err());
+    }
+
+    fn pseudo_random_bytes(len: usize, seed: u8) -> Vec<u8> {
+        // A small-state LCG cycles every 256 bytes and produces highly
+        // regular output that happens to defeat FastCDC's cut condition
+        // entirely; xorshift32 has a long enough period to stand in for
+        // real file content in these tests.
+        let mut state = 0x9E3779B1u32 ^ (seed as u32).wrapping_mul(0x01000193);
+        if state == 0 {
+            state = 1;
+        }
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state & 0xFF) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fastcdc_respects_min_and_max() {
+        let data = pseudo_random_bytes(100_000, 1);
+        let (min, avg, max) = (4 * 1024, 16 * 1024, 64 * 1024);
+
+        let mut offset = 0;
+        while offset < data.len() {
+            let remaining = &data[offset..];
+            let cut = fastcdc_next_cut(remaining, min, avg, max);
+            assert!(cut > 0);
+            assert!(cut <= max);
+            if remaining.len() > min {
+                assert!(cut >= min || cut == remaining.len());
+            }
+            offset += cut;
+        }
+    }
+
+    #[test]
+    fn test_fastcdc_deterministic() {
+        let data = pseudo_random_bytes(50_000, 2);
+        let cut_a = fastcdc_next_cut(&data, 1024, 8192, 32768);
+        let cut_b = fastcdc_next_cut(&data, 1024, 8192, 32768);
+        assert_eq!(cut_a, cut_b);
+    }
+
+    #[test]
+    fn test_fastcdc_boundaries_survive_insertion() {
+        // The dedup property FastCDC exists for: splicing extra bytes into
+        // the middle of a stream should only disturb the chunk(s) around
+        // the splice, not every chunk boundary after it the way fixed-size
+        // chunking would.
+        let (min, avg, max) = (2 * 1024, 8 * 1024, 32 * 1024);
+        let original = pseudo_random_bytes(200_000, 3);
+
+        let mut chunks_original = Vec::new();
+        let mut offset = 0;
+        while offset < original.len() {
+            let cut = fastcdc_next_cut(&original[offset..], min, avg, max);
+            chunks_original.push(original[offset..offset + cut].to_vec());
+            offset += cut;
+        }
+
+        // Splice 37 bytes into the middle of the stream.
+        let splice_point = original.len() / 2;
+        let mut modified = original[..splice_point].to_vec();
+        modified.extend(pseudo_random_bytes(37, 99));
+        modified.extend(&original[splice_point..]);
+
+        let mut chunks_modified = Vec::new();
+        let mut offset = 0;
+        while offset < modified.len() {
+            let cut = fastcdc_next_cut(&modified[offset..], min, avg, max);
+            chunks_modified.push(modified[offset..offset + cut].to_vec());
+            offset += cut;
+        }
+
+        // Chunks before the splice point should be untouched.
+        let unaffected_prefix = chunks_original
+            .iter()
+            .zip(chunks_modified.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        assert!(unaffected_prefix > 0);
+
+        // And the tail should re-synchronize: later chunks should match up
+        // again, rather than every single chunk after the splice differing.
+        // The very last chunk of each stream is excluded since the two
+        // streams differ in total length, so their final cuts (forced by
+        // running out of data) never line up.
+        let matching_suffix = chunks_original[..chunks_original.len() - 1]
+            .iter()
+            .rev()
+            .zip(chunks_modified[..chunks_modified.len() - 1].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        assert!(matching_suffix > 0);
+    }
+
+    #[test]
+    fn test_chunking_strategy_next_chunk_len() {
+        let fixed = ChunkingStrategy::Fixed(1024);
+        assert_eq!(fixed.next_chunk_len(&[0u8; 2048]), 1024);
+        assert_eq!(fixed.next_chunk_len(&[0u8; 500]), 500);
+
+        let fastcdc = ChunkingStrategy::FastCdc {
+            min: 64,
+            avg: 256,
+            max: 1024,
+        };
+        let data = pseudo_random_bytes(4096, 4);
+        let cut = fastcdc.next_chunk_len(&data);
+        assert!((64..=1024).contains(&cut));
+    }
+
+    #[test]
+    fn test_crc32_known_vectors() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn test_chunk_record_roundtrip() {
+        let record = ChunkRecord {
+            index: 7,
+            compression_tag: Compression::None.tag(),
+            ciphertext: vec![1, 2, 3, 4, 5, 6, 7, 8],
+        };
+
+        let mut buffer = Vec::new();
+        record.write_to(&mut buffer).unwrap();
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        let decoded = ChunkRecord::read_from(&mut cursor).unwrap();
+
+        assert_eq!(decoded.index, record.index);
+        assert_eq!(decoded.compression_tag, record.compression_tag);
+        assert_eq!(decoded.ciphertext, record.ciphertext);
+    }
+
+    #[test]
+    fn test_chunk_record_rejects_bad_magic() {
+        let record = ChunkRecord {
+            index: 0,
+            compression_tag: Compression::None.tag(),
+            ciphertext: vec![0xAA; 16],
+        };
+
+        let mut buffer = Vec::new();
+        record.write_to(&mut buffer).unwrap();
+        buffer[0] ^= 0xFF; // corrupt the magic tag
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        assert!(ChunkRecord::read_from(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn test_chunk_record_rejects_bad_crc() {
+        let record = ChunkRecord {
+            index: 0,
+            compression_tag: Compression::None.tag(),
+            ciphertext: vec![0xAA; 16],
+        };
+
+        let mut buffer = Vec::new();
+        record.write_to(&mut buffer).unwrap();
+        let last = buffer.len() - 1;
+        buffer[last] ^= 0xFF; // corrupt a ciphertext byte without touching the magic
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        assert!(ChunkRecord::read_from(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn test_verify_integrity_detects_corrupt_chunk() {
+        let good = ChunkRecord {
+            index: 0,
+            compression_tag: Compression::None.tag(),
+            ciphertext: vec![1, 2, 3, 4],
+        };
+        let bad = ChunkRecord {
+            index: 1,
+            compression_tag: Compression::None.tag(),
+            ciphertext: vec![5, 6, 7, 8],
+        };
+
+        let mut buffer = Vec::new();
+        good.write_to(&mut buffer).unwrap();
+        let second_record_offset = buffer.len() as u64;
+        bad.write_to(&mut buffer).unwrap();
+
+        // Flip a ciphertext byte in the second record so its CRC fails.
+        let last = buffer.len() - 1;
+        buffer[last] ^= 0xFF;
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        let result = StreamHeader::verify_integrity(&mut cursor, 2).unwrap();
+
+        assert_eq!(
+            result,
+            Some(CorruptChunk {
+                index: 1,
+                byte_offset: second_record_offset,
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_integrity_accepts_intact_stream() {
+        let records = [
+            ChunkRecord { index: 0, compression_tag: Compression::None.tag(), ciphertext: vec![1, 2, 3] },
+            ChunkRecord { index: 1, compression_tag: Compression::None.tag(), ciphertext: vec![4, 5, 6, 7] },
+        ];
+
+        let mut buffer = Vec::new();
+        for record in &records {
+            record.write_to(&mut buffer).unwrap();
+        }
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        assert_eq!(StreamHeader::verify_integrity(&mut cursor, 2).unwrap(), None);
+    }
+
+    /// Encrypts `chunks` with [`derive_chunk_nonce`]/[`header_chunk_aad`]
+    /// (the nonce/AAD scheme [`SeekableDecryptor`] expects) and writes them
+    /// out as consecutive [`ChunkRecord`]s, returning the bytes plus the
+    /// offset index a [`StreamHeader`] would carry for them.
+    fn build_indexed_stream(
+        cipher: CipherAlgorithm,
+        key: &[u8],
+        base_nonce: &[u8; NONCE_LEN],
+        chunks: &[&[u8]],
+    ) -> (Vec<u8>, Vec<ChunkIndexEntry>) {
+        let mut buffer = Vec::new();
+        let mut index = Vec::new();
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let chunk_index = i as u64;
+            let nonce = derive_chunk_nonce(base_nonce, chunk_index);
+            let aad = header_chunk_aad(chunk_index);
+            let ciphertext = seal_chunk(cipher, key, &nonce, chunk, &aad).unwrap();
+
+            let record = ChunkRecord {
+                index: chunk_index,
+                compression_tag: Compression::None.tag(),
+                ciphertext: ciphertext.clone(),
+            };
+            let file_offset = buffer.len() as u64;
+            record.write_to(&mut buffer).unwrap();
+
+            index.push(ChunkIndexEntry {
+                chunk_index,
+                file_offset,
+                stored_len: ciphertext.len() as u32,
+                plain_len: chunk.len() as u32,
+            });
+        }
+
+        (buffer, index)
+    }
+
+    #[test]
+    fn test_indexed_stream_writer_drives_fastcdc_chunking() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let base_nonce = [5u8; NONCE_LEN];
+        let config = StreamConfig::new_fastcdc(4 * 1024, 16 * 1024, 64 * 1024).unwrap();
+        let plaintext = pseudo_random_bytes(200_000, 7);
+
+        let mut buffer = Vec::new();
+        let mut writer = IndexedStreamWriter::new(&mut buffer, cipher, key.clone(), base_nonce, config).unwrap();
+        writer.write_plaintext(&plaintext).unwrap();
+        let (chunk_index, total_chunks, _data_map) = writer.finish().unwrap();
+
+        // FastCDC actually drove the cuts: more than one chunk, none
+        // exceeding `max` (the final chunk included, since the writer never
+        // leaves `max` or more bytes buffered for it).
+        assert!(total_chunks > 1);
+        assert_eq!(chunk_index.len() as u64, total_chunks);
+        assert!(chunk_index.iter().all(|entry| entry.plain_len as usize <= 64 * 1024));
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        assert_eq!(StreamHeader::verify_integrity(&mut cursor, total_chunks).unwrap(), None);
+
+        // Sequentially decrypt and reassemble using the same nonce/AAD
+        // scheme the writer sealed chunks with.
+        cursor.set_position(0);
+        let mut decrypted = Vec::new();
+        for expected_index in 0..total_chunks {
+            let record = ChunkRecord::read_from(&mut cursor).unwrap();
+            assert_eq!(record.index, expected_index);
+            let nonce = derive_chunk_nonce(&base_nonce, record.index);
+            let aad = header_chunk_aad(record.index);
+            decrypted.extend(open_chunk(cipher, &key, &nonce, &record.ciphertext, &aad).unwrap());
+        }
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_indexed_stream_writer_convergent_produces_content_addressed_chunks() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let config = StreamConfig::new_fastcdc(4 * 1024, 16 * 1024, 64 * 1024)
+            .unwrap()
+            .with_convergent(true);
+        let plaintext = pseudo_random_bytes(50_000, 11);
+
+        // Two independent writers, different base nonces, same plaintext and
+        // key: convergent encryption should derive key/nonce from content
+        // rather than the stream's base nonce, so both runs must produce
+        // identical ciphertext and `DataMap` content hashes.
+        let mut buffer_a = Vec::new();
+        let writer_a =
+            IndexedStreamWriter::new(&mut buffer_a, cipher, key.clone(), [1u8; NONCE_LEN], config.clone()).unwrap();
+        let mut buffer_b = Vec::new();
+        let writer_b = IndexedStreamWriter::new(&mut buffer_b, cipher, key, [2u8; NONCE_LEN], config).unwrap();
+
+        let (_, total_chunks_a, data_map_a) = {
+            let mut w = writer_a;
+            w.write_plaintext(&plaintext).unwrap();
+            w.finish().unwrap()
+        };
+        let (_, total_chunks_b, data_map_b) = {
+            let mut w = writer_b;
+            w.write_plaintext(&plaintext).unwrap();
+            w.finish().unwrap()
+        };
+
+        assert_eq!(total_chunks_a, total_chunks_b);
+        assert_eq!(buffer_a, buffer_b);
+        assert_eq!(data_map_a, data_map_b);
+        assert!(!data_map_a.entries.is_empty());
+        assert_eq!(data_map_a.entries.len() as u64, total_chunks_a);
+    }
+
+    #[test]
+    fn test_verify_integrity_detects_corruption_in_indexed_stream_writer_output() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let base_nonce = [9u8; NONCE_LEN];
+        let config = StreamConfig::new_fastcdc(4 * 1024, 16 * 1024, 64 * 1024).unwrap();
+        let plaintext = pseudo_random_bytes(50_000, 3);
+
+        let mut buffer = Vec::new();
+        let mut writer = IndexedStreamWriter::new(&mut buffer, cipher, key, base_nonce, config).unwrap();
+        writer.write_plaintext(&plaintext).unwrap();
+        let (chunk_index, total_chunks, _data_map) = writer.finish().unwrap();
+        assert!(total_chunks > 1);
+
+        // Flip a ciphertext byte inside the second chunk's record.
+        let second = &chunk_index[1];
+        let corrupt_at = second.file_offset + CHUNK_RECORD_OVERHEAD;
+        buffer[corrupt_at as usize] ^= 0xFF;
+
+        let mut cursor = std::io::Cursor::new(buffer);
+        let result = StreamHeader::verify_integrity(&mut cursor, total_chunks).unwrap();
+
+        assert_eq!(
+            result,
+            Some(CorruptChunk {
+                index: 1,
+                byte_offset: second.file_offset,
+            })
+        );
+    }
+
+    #[test]
+    fn test_seekable_decryptor_reads_middle_chunk_only() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let base_nonce = [11u8; NONCE_LEN];
+        let chunks: [&[u8]; 3] = [b"first chunk data", b"second chunk data!!", b"third and final"];
+
+        let (buffer, index) = build_indexed_stream(cipher, &key, &base_nonce, &chunks);
+
+        let cursor = std::io::Cursor::new(buffer);
+        let mut decryptor = SeekableDecryptor::new(cursor, cipher, key, base_nonce, 0, index).unwrap();
+
+        // Request a range that falls entirely within the second chunk.
+        let start = chunks[0].len() as u64 + 2;
+        let end = start + 5;
+        let result = decryptor.read_range(start, end).unwrap();
+        assert_eq!(result, &chunks[1][2..7]);
+    }
+
+    #[test]
+    fn test_seekable_decryptor_spans_multiple_chunks() {
+        let cipher = CipherAlgorithm::ChaCha20Poly1305;
+        let key = test_key();
+        let base_nonce = [22u8; NONCE_LEN];
+        let chunks: [&[u8]; 3] = [b"aaaaaaaaaa", b"bbbbbbbbbb", b"cccccccccc"];
+
+        let (buffer, index) = build_indexed_stream(cipher, &key, &base_nonce, &chunks);
+
+        let cursor = std::io::Cursor::new(buffer);
+        let mut decryptor = SeekableDecryptor::new(cursor, cipher, key, base_nonce, 0, index).unwrap();
+
+        // Range spans the tail of chunk 0 and the head of chunk 1.
+        let result = decryptor.read_range(8, 12).unwrap();
+        assert_eq!(result, b"aabb");
+    }
+
+    #[test]
+    fn test_seekable_decryptor_rejects_tampered_chunk() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let base_nonce = [33u8; NONCE_LEN];
+        let chunks: [&[u8]; 2] = [b"untampered first chunk", b"this one gets corrupted!"];
+
+        let (mut buffer, index) = build_indexed_stream(cipher, &key, &base_nonce, &chunks);
+
+        // Corrupt a ciphertext byte in the second chunk's record.
+        let last = buffer.len() - 1;
+        buffer[last] ^= 0xFF;
+
+        let cursor = std::io::Cursor::new(buffer);
+        let mut decryptor = SeekableDecryptor::new(cursor, cipher, key, base_nonce, 0, index).unwrap();
+
+        let start = chunks[0].len() as u64;
+        assert!(decryptor.read_range(start, start + 1).is_err());
+    }
+
+    #[test]
+    fn test_seekable_decryptor_reads_indexed_stream_writer_output() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let base_nonce = [44u8; NONCE_LEN];
+        let config = StreamConfig::new_fastcdc(2 * 1024, 8 * 1024, 32 * 1024).unwrap();
+        let plaintext = pseudo_random_bytes(100_000, 11);
+
+        let mut buffer = Vec::new();
+        let mut writer = IndexedStreamWriter::new(&mut buffer, cipher, key.clone(), base_nonce, config).unwrap();
+        writer.write_plaintext(&plaintext).unwrap();
+        let (chunk_index, total_chunks, _data_map) = writer.finish().unwrap();
+        assert!(total_chunks > 1);
+
+        let cursor = std::io::Cursor::new(buffer);
+        let mut decryptor = SeekableDecryptor::new(cursor, cipher, key, base_nonce, 0, chunk_index).unwrap();
+
+        // Arbitrary mid-stream range spanning a chunk boundary, read back
+        // without decrypting the whole stream.
+        let start = plaintext.len() as u64 / 3;
+        let end = start + 777;
+        let result = decryptor.read_range(start, end).unwrap();
+        assert_eq!(result, &plaintext[start as usize..end as usize]);
+
+        // And the very first and last bytes, each in their own chunk.
+        assert_eq!(decryptor.read_range(0, 1).unwrap(), &plaintext[0..1]);
+        let last = plaintext.len() as u64;
+        assert_eq!(decryptor.read_range(last - 1, last).unwrap(), &plaintext[plaintext.len() - 1..]);
+    }
+
+    #[test]
+    fn test_seekable_decryptor_reads_compressed_indexed_stream() {
+        let cipher = CipherAlgorithm::Aes256Gcm;
+        let key = test_key();
+        let base_nonce = [55u8; NONCE_LEN];
+        let config = StreamConfig::new_fastcdc(2 * 1024, 8 * 1024, 32 * 1024)
+            .unwrap()
+            .with_compression(Compression::Zstd { level: 3 });
+        // Highly repetitive, so chunks actually shrink under compression
+        // rather than falling back to raw storage.
+        let plaintext: Vec<u8> = (0..100_000).map(|i| (i % 17) as u8).collect();
+
+        let mut buffer = Vec::new();
+        let mut writer = IndexedStreamWriter::new(&mut buffer, cipher, key.clone(), base_nonce, config).unwrap();
+        writer.write_plaintext(&plaintext).unwrap();
+        let (chunk_index, total_chunks, _data_map) = writer.finish().unwrap();
+        assert!(total_chunks > 1);
+
+        // At least one chunk actually compressed, i.e. its stored length is
+        // shorter than its plaintext length plus the AEAD tag.
+        assert!(chunk_index
+            .iter()
+            .any(|entry| (entry.stored_len as u64) < entry.plain_len as u64 + 16));
+
+        let cursor = std::io::Cursor::new(buffer);
+        let mut decryptor = SeekableDecryptor::new(cursor, cipher, key, base_nonce, 0, chunk_index).unwrap();
+
+        let start = plaintext.len() as u64 / 2;
+        let end = start + 500;
+        let result = decryptor.read_range(start, end).unwrap();
+        assert_eq!(result, &plaintext[start as usize..end as usize]);
     }
 }
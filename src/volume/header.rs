@@ -8,6 +8,10 @@ use serde_big_array::BigArray;
 use std::io::{self, Read, Write};
 use thiserror::Error;
 
+use crate::crypto::aes_gcm::AesGcmEncryptor;
+use crate::crypto::chacha20::{ChaCha20Poly1305Encryptor, XChaCha20Poly1305Encryptor};
+use crate::crypto::Encryptor;
+
 /// Magic bytes to identify Secure Cryptor volume files
 /// "SECVOL01" in ASCII
 const MAGIC: [u8; 8] = [0x53, 0x45, 0x43, 0x56, 0x4F, 0x4C, 0x30, 0x31];
@@ -19,48 +23,196 @@ const VERSION: u32 = 1;
 pub const HEADER_SIZE: usize = 4096;
 
 /// Cipher algorithm identifier
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum CipherAlgorithm {
     /// AES-256-GCM (default)
     Aes256Gcm = 1,
+
+    /// ChaCha20-Poly1305, a constant-time software cipher for platforms
+    /// without AES hardware acceleration
+    ChaCha20Poly1305 = 2,
+
+    /// XChaCha20-Poly1305, using an extended 24-byte nonce for
+    /// misuse-resistant random nonce generation
+    XChaCha20Poly1305 = 3,
 }
 
-/// Volume header containing all metadata
+impl CipherAlgorithm {
+    /// Returns the nonce length in bytes required by this cipher
+    pub fn nonce_len(self) -> usize {
+        match self {
+            CipherAlgorithm::Aes256Gcm => 12,
+            CipherAlgorithm::ChaCha20Poly1305 => 12,
+            CipherAlgorithm::XChaCha20Poly1305 => 24,
+        }
+    }
+}
+
+impl std::convert::TryFrom<u8> for CipherAlgorithm {
+    type Error = HeaderError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(CipherAlgorithm::Aes256Gcm),
+            2 => Ok(CipherAlgorithm::ChaCha20Poly1305),
+            3 => Ok(CipherAlgorithm::XChaCha20Poly1305),
+            other => Err(HeaderError::UnknownCipher(other)),
+        }
+    }
+}
+
+/// Key-derivation function identifier
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum KdfAlgorithm {
+    /// Argon2id (default, recommended for most devices)
+    Argon2id = 1,
+
+    /// PBKDF2-HMAC-SHA256, for devices where Argon2's memory cost is
+    /// impractical
+    Pbkdf2HmacSha256 = 2,
+
+    /// scrypt
+    Scrypt = 3,
+}
+
+impl std::convert::TryFrom<u8> for KdfAlgorithm {
+    type Error = HeaderError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(KdfAlgorithm::Argon2id),
+            2 => Ok(KdfAlgorithm::Pbkdf2HmacSha256),
+            3 => Ok(KdfAlgorithm::Scrypt),
+            other => Err(HeaderError::UnknownKdf(other)),
+        }
+    }
+}
+
+/// Parameters for the key-derivation function used to protect the volume.
+///
+/// The meaning of each field depends on `KdfAlgorithm`:
+/// - Argon2id: `cost` is memory cost in KiB, `time_cost` is iterations,
+///   `parallelism` is the lane count
+/// - PBKDF2-HMAC-SHA256: `time_cost` is the iteration count; `cost` and
+///   `parallelism` are unused (zero)
+/// - scrypt: `cost` is the CPU/memory cost parameter `N`, `time_cost` is the
+///   block size `r`, `parallelism` is the parallelization parameter `p`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory/CPU cost parameter (meaning depends on the algorithm)
+    pub cost: u32,
+    /// Time cost parameter (meaning depends on the algorithm)
+    pub time_cost: u32,
+    /// Parallelism parameter (meaning depends on the algorithm)
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Recommended parameters for Argon2id: 64 MiB memory, 3 iterations, 1 lane
+    pub fn argon2id_default() -> Self {
+        Self {
+            cost: 65536,
+            time_cost: 3,
+            parallelism: 1,
+        }
+    }
+
+    /// Recommended parameters for PBKDF2-HMAC-SHA256: 600,000 iterations
+    /// (OWASP 2023 recommendation)
+    pub fn pbkdf2_default() -> Self {
+        Self {
+            cost: 0,
+            time_cost: 600_000,
+            parallelism: 0,
+        }
+    }
+
+    /// Recommended parameters for scrypt: N=2^17, r=8, p=1
+    pub fn scrypt_default() -> Self {
+        Self {
+            cost: 1 << 17,
+            time_cost: 8,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Fixed plaintext marker whose AEAD tag, keyed by the volume's derived key,
+/// acts as a password-verification check in the superblock. `mount` can
+/// recompute this tag from a candidate password and compare it before ever
+/// touching the (much more expensive to fail on) encrypted payload, telling
+/// a wrong password apart from a corrupt/tampered volume.
+const VERIFY_MARKER: &[u8] = b"secure-cryptor-header-verify-v1";
+
+/// Length in bytes of an AEAD authentication tag (same for every cipher we
+/// support: AES-256-GCM, ChaCha20-Poly1305 and XChaCha20-Poly1305 all use a
+/// 128-bit tag)
+const TAG_LEN: usize = 16;
+
+/// The portion of the header that must remain in plaintext: everything
+/// needed to derive the key and authenticate a password attempt before the
+/// encrypted payload can be touched.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VolumeHeader {
-    /// Magic bytes for file identification
+struct HeaderPrefix {
     magic: [u8; 8],
-
-    /// Header format version
     version: u32,
-
-    /// Cipher algorithm used for encryption
     cipher: CipherAlgorithm,
-
-    /// Salt for key derivation (32 bytes for Argon2id)
+    kdf: KdfAlgorithm,
+    kdf_params: KdfParams,
     salt: [u8; 32],
+    header_iv: [u8; 24],
+    /// Nonce the encrypted payload below was sealed under for *this*
+    /// serialization; freshly randomized on every [`VolumeHeader::to_bytes`]
+    /// call so re-serializing a header whose payload changed (e.g. after
+    /// [`VolumeHeader::touch`]) never reuses a (key, nonce) pair.
+    payload_nonce: [u8; 24],
+    /// Keyed verification tag; see [`VERIFY_MARKER`]
+    verify_tag: [u8; TAG_LEN],
+}
 
-    /// Initialization vector for header encryption (12 bytes for AES-GCM)
-    header_iv: [u8; 12],
-
-    /// Total volume size in bytes (excluding header)
+/// The sensitive portion of the header, encrypted under the derived key
+/// before it is written out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HeaderPayload {
     volume_size: u64,
-
-    /// Sector size in bytes (typically 512 or 4096)
     sector_size: u32,
-
-    /// Creation timestamp (Unix epoch seconds)
     created_at: u64,
-
-    /// Last modification timestamp (Unix epoch seconds)
     modified_at: u64,
-
-    /// Reserved space for future use (256 bytes)
     #[serde(with = "BigArray")]
     reserved: [u8; 256],
 }
 
+/// Volume header containing all metadata
+///
+/// Only [`HeaderPrefix`] is ever written to disk in plaintext; [`HeaderPayload`]
+/// is AEAD-encrypted under the volume's derived key whenever the header is
+/// serialized, using a freshly randomized nonce recorded alongside it in
+/// [`HeaderPrefix::payload_nonce`] (see [`truncate_payload_nonce`]) — never
+/// the fixed `header_iv`, which would repeat across every re-serialization
+/// of a changed payload. In memory the header always holds plaintext
+/// fields, so callers never have to thread a key through the ordinary
+/// accessors.
+#[derive(Debug, Clone)]
+pub struct VolumeHeader {
+    magic: [u8; 8],
+    version: u32,
+    cipher: CipherAlgorithm,
+    kdf: KdfAlgorithm,
+    kdf_params: KdfParams,
+    salt: [u8; 32],
+    header_iv: [u8; 24],
+    verify_tag: [u8; TAG_LEN],
+    volume_size: u64,
+    sector_size: u32,
+    created_at: u64,
+    modified_at: u64,
+    reserved: [u8; 256],
+}
+
 /// Errors that can occur when working with volume headers
 #[derive(Debug, Error)]
 pub enum HeaderError {
@@ -83,6 +235,86 @@ pub enum HeaderError {
     /// Header size mismatch
     #[error("Header size mismatch: expected {expected}, got {actual}")]
     SizeMismatch { expected: usize, actual: usize },
+
+    /// Unknown cipher algorithm identifier
+    #[error("Unknown cipher algorithm identifier: {0}")]
+    UnknownCipher(u8),
+
+    /// Unknown KDF algorithm identifier
+    #[error("Unknown KDF algorithm identifier: {0}")]
+    UnknownKdf(u8),
+
+    /// The header's encrypted payload failed to authenticate, meaning the
+    /// volume is corrupt or has been tampered with (the password itself
+    /// checked out via the superblock verification tag)
+    #[error("Header authentication failed: volume is corrupt or has been tampered with")]
+    TamperedHeader,
+
+    /// The supplied password does not match the one the volume was created
+    /// with (checked via the superblock verification tag, before the
+    /// encrypted payload is ever touched)
+    #[error("Incorrect password")]
+    WrongPassword,
+
+    /// Underlying AEAD cipher error
+    #[error("Header encryption error: {0}")]
+    Cryptography(String),
+}
+
+/// Dispatches to the AEAD encryptor selected by `cipher`
+fn aead_encrypt(cipher: CipherAlgorithm, key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, HeaderError> {
+    let result = match cipher {
+        CipherAlgorithm::Aes256Gcm => AesGcmEncryptor.encrypt(key, nonce, plaintext),
+        CipherAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305Encryptor.encrypt(key, nonce, plaintext),
+        CipherAlgorithm::XChaCha20Poly1305 => XChaCha20Poly1305Encryptor.encrypt(key, nonce, plaintext),
+    };
+    result.map_err(|e| HeaderError::Cryptography(e.to_string()))
+}
+
+/// Dispatches to the AEAD decryptor selected by `cipher`
+fn aead_decrypt(cipher: CipherAlgorithm, key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, HeaderError> {
+    let result = match cipher {
+        CipherAlgorithm::Aes256Gcm => AesGcmEncryptor.decrypt(key, nonce, ciphertext),
+        CipherAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305Encryptor.decrypt(key, nonce, ciphertext),
+        CipherAlgorithm::XChaCha20Poly1305 => XChaCha20Poly1305Encryptor.decrypt(key, nonce, ciphertext),
+    };
+    result.map_err(|_| HeaderError::TamperedHeader)
+}
+
+/// Truncates a freshly randomized `payload_nonce` (see [`HeaderPrefix`]) to
+/// the `cipher`'s actual nonce length, for sealing/opening [`HeaderPayload`].
+fn truncate_payload_nonce(payload_nonce: &[u8; 24], cipher: CipherAlgorithm) -> Vec<u8> {
+    payload_nonce[..cipher.nonce_len()].to_vec()
+}
+
+/// Derives the nonce used to compute the superblock verification tag from
+/// the header IV, truncated to the `cipher`'s nonce length, with the low bit
+/// of the first byte flipped. The encrypted payload does not use this nonce:
+/// it is sealed under the separately randomized
+/// [`HeaderPrefix::payload_nonce`].
+fn verify_nonce(header_iv: &[u8], cipher: CipherAlgorithm) -> Vec<u8> {
+    let mut nonce = header_iv[..cipher.nonce_len()].to_vec();
+    nonce[0] ^= 0x01;
+    nonce
+}
+
+/// Computes the keyed superblock verification tag for `key`/`cipher`/`nonce`
+fn compute_verify_tag(cipher: CipherAlgorithm, key: &[u8], header_iv: &[u8]) -> Result<[u8; TAG_LEN], HeaderError> {
+    let nonce = verify_nonce(header_iv, cipher);
+    let sealed = aead_encrypt(cipher, key, &nonce, VERIFY_MARKER)?;
+    let tag = &sealed[sealed.len() - TAG_LEN..];
+    let mut out = [0u8; TAG_LEN];
+    out.copy_from_slice(tag);
+    Ok(out)
+}
+
+/// Constant-time byte slice comparison, used to compare verification tags
+/// without leaking timing information about where they first differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
 impl VolumeHeader {
@@ -92,47 +324,104 @@ impl VolumeHeader {
     ///
     /// * `volume_size` - Total size of the encrypted volume in bytes
     /// * `sector_size` - Size of each sector in bytes (typically 512 or 4096)
+    /// * `cipher` - Cipher algorithm to encrypt the volume with
+    /// * `kdf` - Key-derivation function used to derive the volume key
+    /// * `kdf_params` - Parameters the KDF was run with
     /// * `salt` - 32-byte salt for key derivation
-    /// * `header_iv` - 12-byte IV for header encryption
+    /// * `header_iv` - IV the superblock verification tag's nonce is derived
+    ///   from (the payload is sealed under a fresh random nonce instead); only
+    ///   the first `cipher.nonce_len()` bytes are used, the rest are ignored
+    /// * `key` - The key derived from the volume password, used to compute
+    ///   the superblock verification tag (see [`VolumeHeader::verify_password`])
     ///
     /// # Returns
     ///
     /// A new `VolumeHeader` instance
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if computing the verification tag fails
     pub fn new(
         volume_size: u64,
         sector_size: u32,
+        cipher: CipherAlgorithm,
+        kdf: KdfAlgorithm,
+        kdf_params: KdfParams,
         salt: [u8; 32],
-        header_iv: [u8; 12],
-    ) -> Self {
+        header_iv: [u8; 24],
+        key: &[u8],
+    ) -> Result<Self, HeaderError> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .expect("System time before Unix epoch")
             .as_secs();
 
-        Self {
+        let verify_tag = compute_verify_tag(cipher, key, &header_iv)?;
+
+        Ok(Self {
             magic: MAGIC,
             version: VERSION,
-            cipher: CipherAlgorithm::Aes256Gcm,
+            cipher,
+            kdf,
+            kdf_params,
             salt,
             header_iv,
+            verify_tag,
             volume_size,
             sector_size,
             created_at: now,
             modified_at: now,
             reserved: [0u8; 256],
-        }
+        })
     }
 
-    /// Serializes the header to bytes
+    /// Checks whether `key` is the key this header was created with, without
+    /// decrypting the (much larger) encrypted payload.
     ///
-    /// The header is serialized to exactly HEADER_SIZE bytes, with
+    /// `mount` should call this before attempting to decrypt anything, so a
+    /// wrong password is reported distinctly from a corrupt/tampered volume.
+    pub fn verify_password(&self, key: &[u8]) -> Result<bool, HeaderError> {
+        let expected = compute_verify_tag(self.cipher, key, &self.header_iv)?;
+        Ok(constant_time_eq(&expected, &self.verify_tag))
+    }
+
+    /// Serializes the header to bytes, AEAD-encrypting the sensitive
+    /// payload (volume size, sector size, timestamps and reserved space)
+    /// under `key`. The resulting blob is exactly HEADER_SIZE bytes, with
     /// padding added if necessary.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// A byte vector of exactly HEADER_SIZE bytes
-    pub fn to_bytes(&self) -> Result<Vec<u8>, HeaderError> {
-        let mut serialized = bincode::serialize(self)?;
+    /// Returns an error if serialization, encryption, or padding fails
+    pub fn to_bytes(&self, key: &[u8]) -> Result<Vec<u8>, HeaderError> {
+        let mut payload_nonce = [0u8; 24];
+        getrandom::fill(&mut payload_nonce[..self.cipher.nonce_len()])
+            .expect("failed to generate random nonce");
+
+        let prefix = HeaderPrefix {
+            magic: self.magic,
+            version: self.version,
+            cipher: self.cipher,
+            kdf: self.kdf,
+            kdf_params: self.kdf_params,
+            salt: self.salt,
+            header_iv: self.header_iv,
+            payload_nonce,
+            verify_tag: self.verify_tag,
+        };
+        let payload = HeaderPayload {
+            volume_size: self.volume_size,
+            sector_size: self.sector_size,
+            created_at: self.created_at,
+            modified_at: self.modified_at,
+            reserved: self.reserved,
+        };
+
+        let mut serialized = bincode::serialize(&prefix)?;
+        let payload_bytes = bincode::serialize(&payload)?;
+        let nonce = truncate_payload_nonce(&payload_nonce, self.cipher);
+        let sealed_payload = aead_encrypt(self.cipher, key, &nonce, &payload_bytes)?;
+        serialized.extend_from_slice(&sealed_payload);
 
         // Ensure the header is exactly HEADER_SIZE bytes
         if serialized.len() > HEADER_SIZE {
@@ -148,15 +437,14 @@ impl VolumeHeader {
         Ok(serialized)
     }
 
-    /// Deserializes a header from bytes
+    /// Deserializes a header from bytes, checking the superblock
+    /// verification tag and then decrypting the sensitive payload under
+    /// `key`.
     ///
     /// # Arguments
     ///
     /// * `bytes` - Byte slice containing the serialized header
-    ///
-    /// # Returns
-    ///
-    /// A deserialized `VolumeHeader` instance
+    /// * `key` - The key derived from the candidate volume password
     ///
     /// # Errors
     ///
@@ -164,8 +452,10 @@ impl VolumeHeader {
     /// - The bytes are not exactly HEADER_SIZE long
     /// - The magic bytes are invalid
     /// - The version is unsupported
+    /// - `key` does not match the verification tag ([`HeaderError::WrongPassword`])
+    /// - The encrypted payload fails to authenticate ([`HeaderError::TamperedHeader`])
     /// - Deserialization fails
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HeaderError> {
+    pub fn from_bytes(bytes: &[u8], key: &[u8]) -> Result<Self, HeaderError> {
         if bytes.len() != HEADER_SIZE {
             return Err(HeaderError::SizeMismatch {
                 expected: HEADER_SIZE,
@@ -173,53 +463,73 @@ impl VolumeHeader {
             });
         }
 
-        let header: Self = bincode::deserialize(bytes)?;
+        let prefix: HeaderPrefix = bincode::deserialize(bytes)?;
 
-        // Validate magic bytes
-        if header.magic != MAGIC {
+        if prefix.magic != MAGIC {
             return Err(HeaderError::InvalidMagic);
         }
+        if prefix.version != VERSION {
+            return Err(HeaderError::UnsupportedVersion(prefix.version));
+        }
 
-        // Check version compatibility
-        if header.version != VERSION {
-            return Err(HeaderError::UnsupportedVersion(header.version));
+        let expected_tag = compute_verify_tag(prefix.cipher, key, &prefix.header_iv)?;
+        if !constant_time_eq(&expected_tag, &prefix.verify_tag) {
+            return Err(HeaderError::WrongPassword);
         }
 
-        Ok(header)
+        let prefix_len = bincode::serialized_size(&prefix)? as usize;
+        let payload_plain_len = bincode::serialized_size(&HeaderPayload {
+            volume_size: 0,
+            sector_size: 0,
+            created_at: 0,
+            modified_at: 0,
+            reserved: [0u8; 256],
+        })? as usize;
+        let sealed_len = payload_plain_len + TAG_LEN;
+        let sealed_payload = &bytes[prefix_len..prefix_len + sealed_len];
+
+        let nonce = truncate_payload_nonce(&prefix.payload_nonce, prefix.cipher);
+        let payload_bytes = aead_decrypt(prefix.cipher, key, &nonce, sealed_payload)?;
+        let payload: HeaderPayload = bincode::deserialize(&payload_bytes)?;
+
+        Ok(Self {
+            magic: prefix.magic,
+            version: prefix.version,
+            cipher: prefix.cipher,
+            kdf: prefix.kdf,
+            kdf_params: prefix.kdf_params,
+            salt: prefix.salt,
+            header_iv: prefix.header_iv,
+            verify_tag: prefix.verify_tag,
+            volume_size: payload.volume_size,
+            sector_size: payload.sector_size,
+            created_at: payload.created_at,
+            modified_at: payload.modified_at,
+            reserved: payload.reserved,
+        })
     }
 
-    /// Writes the header to a writer
-    ///
-    /// # Arguments
-    ///
-    /// * `writer` - The writer to write the header to
+    /// Writes the header to a writer, encrypting its payload under `key`
     ///
     /// # Errors
     ///
-    /// Returns an error if serialization or writing fails
-    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), HeaderError> {
-        let bytes = self.to_bytes()?;
+    /// Returns an error if serialization, encryption, or writing fails
+    pub fn write_to<W: Write>(&self, writer: &mut W, key: &[u8]) -> Result<(), HeaderError> {
+        let bytes = self.to_bytes(key)?;
         writer.write_all(&bytes)?;
         Ok(())
     }
 
-    /// Reads a header from a reader
-    ///
-    /// # Arguments
-    ///
-    /// * `reader` - The reader to read the header from
-    ///
-    /// # Returns
-    ///
-    /// A deserialized `VolumeHeader` instance
+    /// Reads a header from a reader, checking the password and decrypting
+    /// its payload under `key`
     ///
     /// # Errors
     ///
-    /// Returns an error if reading or deserialization fails
-    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, HeaderError> {
+    /// Returns an error if reading, password verification, or decryption fails
+    pub fn read_from<R: Read>(reader: &mut R, key: &[u8]) -> Result<Self, HeaderError> {
         let mut bytes = vec![0u8; HEADER_SIZE];
         reader.read_exact(&mut bytes)?;
-        Self::from_bytes(&bytes)
+        Self::from_bytes(&bytes, key)
     }
 
     /// Updates the modification timestamp to the current time
@@ -235,11 +545,18 @@ impl VolumeHeader {
         &self.salt
     }
 
-    /// Returns the header IV
-    pub fn header_iv(&self) -> &[u8; 12] {
+    /// Returns the full header IV buffer (24 bytes as passed to
+    /// [`VolumeHeader::new`]; bytes beyond the cipher's nonce length are unused)
+    pub fn header_iv(&self) -> &[u8; 24] {
         &self.header_iv
     }
 
+    /// Returns only the bytes of the header IV that are meaningful for the
+    /// volume's selected cipher
+    pub fn header_nonce(&self) -> &[u8] {
+        &self.header_iv[..self.cipher.nonce_len()]
+    }
+
     /// Returns the total volume size in bytes
     pub fn volume_size(&self) -> u64 {
         self.volume_size
@@ -264,6 +581,16 @@ impl VolumeHeader {
     pub fn cipher(&self) -> CipherAlgorithm {
         self.cipher
     }
+
+    /// Returns the key-derivation function used to protect this volume
+    pub fn kdf(&self) -> KdfAlgorithm {
+        self.kdf
+    }
+
+    /// Returns the parameters the KDF was run with
+    pub fn kdf_params(&self) -> KdfParams {
+        self.kdf_params
+    }
 }
 
 #[cfg(test)]
@@ -271,11 +598,13 @@ mod tests {
     use super::*;
     use std::io::Cursor;
 
+    const KEY: [u8; 32] = [9u8; 32];
+
     #[test]
     fn test_header_creation() {
         let salt = [1u8; 32];
-        let iv = [2u8; 12];
-        let header = VolumeHeader::new(1024 * 1024 * 1024, 4096, salt, iv);
+        let iv = [2u8; 24];
+        let header = VolumeHeader::new(1024 * 1024 * 1024, 4096, CipherAlgorithm::Aes256Gcm, KdfAlgorithm::Argon2id, KdfParams::argon2id_default(), salt, iv, &KEY).unwrap();
 
         assert_eq!(header.magic, MAGIC);
         assert_eq!(header.version, VERSION);
@@ -289,30 +618,68 @@ mod tests {
     #[test]
     fn test_header_serialization() {
         let salt = [1u8; 32];
-        let iv = [2u8; 12];
-        let header = VolumeHeader::new(1024 * 1024 * 1024, 4096, salt, iv);
+        let iv = [2u8; 24];
+        let header = VolumeHeader::new(1024 * 1024 * 1024, 4096, CipherAlgorithm::Aes256Gcm, KdfAlgorithm::Argon2id, KdfParams::argon2id_default(), salt, iv, &KEY).unwrap();
 
-        let bytes = header.to_bytes().unwrap();
+        let bytes = header.to_bytes(&KEY).unwrap();
         assert_eq!(bytes.len(), HEADER_SIZE);
 
-        let deserialized = VolumeHeader::from_bytes(&bytes).unwrap();
+        let deserialized = VolumeHeader::from_bytes(&bytes, &KEY).unwrap();
         assert_eq!(deserialized.salt, header.salt);
         assert_eq!(deserialized.header_iv, header.header_iv);
         assert_eq!(deserialized.volume_size, header.volume_size);
         assert_eq!(deserialized.sector_size, header.sector_size);
     }
 
+    #[test]
+    fn test_touch_then_reserialize_uses_fresh_payload_nonce() {
+        let salt = [1u8; 32];
+        let iv = [2u8; 24];
+        let mut header = VolumeHeader::new(1024, 512, CipherAlgorithm::Aes256Gcm, KdfAlgorithm::Argon2id, KdfParams::argon2id_default(), salt, iv, &KEY).unwrap();
+
+        let first_bytes = header.to_bytes(&KEY).unwrap();
+
+        header.touch();
+        let second_bytes = header.to_bytes(&KEY).unwrap();
+
+        // Same header_iv (it's immutable), but the random payload nonce
+        // recorded in the plaintext prefix must differ between the two
+        // serializations, or the changed `modified_at` payload would be
+        // sealed under a reused (key, nonce) pair.
+        let prefix_len = bincode::serialized_size(&HeaderPrefix {
+            magic: MAGIC,
+            version: VERSION,
+            cipher: CipherAlgorithm::Aes256Gcm,
+            kdf: KdfAlgorithm::Argon2id,
+            kdf_params: KdfParams::argon2id_default(),
+            salt,
+            header_iv: iv,
+            payload_nonce: [0u8; 24],
+            verify_tag: [0u8; TAG_LEN],
+        })
+        .unwrap() as usize;
+        assert_ne!(first_bytes[..prefix_len], second_bytes[..prefix_len]);
+
+        // Both serializations must still decrypt correctly under their own
+        // (freshly randomized) nonce.
+        let first_decoded = VolumeHeader::from_bytes(&first_bytes, &KEY).unwrap();
+        let second_decoded = VolumeHeader::from_bytes(&second_bytes, &KEY).unwrap();
+        assert_eq!(second_decoded.modified_at, header.modified_at);
+        assert_eq!(first_decoded.volume_size, header.volume_size);
+        assert_eq!(second_decoded.volume_size, header.volume_size);
+    }
+
     #[test]
     fn test_header_write_read() {
         let salt = [3u8; 32];
-        let iv = [4u8; 12];
-        let header = VolumeHeader::new(2 * 1024 * 1024 * 1024, 512, salt, iv);
+        let iv = [4u8; 24];
+        let header = VolumeHeader::new(2 * 1024 * 1024 * 1024, 512, CipherAlgorithm::Aes256Gcm, KdfAlgorithm::Argon2id, KdfParams::argon2id_default(), salt, iv, &KEY).unwrap();
 
         let mut buffer = Vec::new();
-        header.write_to(&mut buffer).unwrap();
+        header.write_to(&mut buffer, &KEY).unwrap();
 
         let mut cursor = Cursor::new(buffer);
-        let read_header = VolumeHeader::read_from(&mut cursor).unwrap();
+        let read_header = VolumeHeader::read_from(&mut cursor, &KEY).unwrap();
 
         assert_eq!(read_header.salt, salt);
         assert_eq!(read_header.header_iv, iv);
@@ -320,20 +687,51 @@ mod tests {
         assert_eq!(read_header.sector_size, 512);
     }
 
+    #[test]
+    fn test_cipher_nonce_len() {
+        assert_eq!(CipherAlgorithm::Aes256Gcm.nonce_len(), 12);
+        assert_eq!(CipherAlgorithm::ChaCha20Poly1305.nonce_len(), 12);
+        assert_eq!(CipherAlgorithm::XChaCha20Poly1305.nonce_len(), 24);
+    }
+
+    #[test]
+    fn test_kdf_roundtrip() {
+        let salt = [5u8; 32];
+        let iv = [6u8; 24];
+        let params = KdfParams::pbkdf2_default();
+        let header = VolumeHeader::new(
+            1024 * 1024,
+            4096,
+            CipherAlgorithm::Aes256Gcm,
+            KdfAlgorithm::Pbkdf2HmacSha256,
+            params,
+            salt,
+            iv,
+            &KEY,
+        )
+        .unwrap();
+
+        let bytes = header.to_bytes(&KEY).unwrap();
+        let decoded = VolumeHeader::from_bytes(&bytes, &KEY).unwrap();
+
+        assert_eq!(decoded.kdf(), KdfAlgorithm::Pbkdf2HmacSha256);
+        assert_eq!(decoded.kdf_params(), params);
+    }
+
     #[test]
     fn test_invalid_magic() {
         let mut bytes = vec![0u8; HEADER_SIZE];
         bytes[0..8].copy_from_slice(b"INVALID!");
 
-        let result = VolumeHeader::from_bytes(&bytes);
+        let result = VolumeHeader::from_bytes(&bytes, &KEY);
         assert!(matches!(result, Err(HeaderError::InvalidMagic)));
     }
 
     #[test]
     fn test_touch() {
         let salt = [1u8; 32];
-        let iv = [2u8; 12];
-        let mut header = VolumeHeader::new(1024, 512, salt, iv);
+        let iv = [2u8; 24];
+        let mut header = VolumeHeader::new(1024, 512, CipherAlgorithm::Aes256Gcm, KdfAlgorithm::Argon2id, KdfParams::argon2id_default(), salt, iv, &KEY).unwrap();
 
         let original_modified = header.modified_at;
         std::thread::sleep(std::time::Duration::from_secs(1));
@@ -342,4 +740,51 @@ mod tests {
         assert!(header.modified_at > original_modified);
         assert_eq!(header.created_at, original_modified);
     }
+
+    #[test]
+    fn test_verify_password() {
+        let salt = [7u8; 32];
+        let iv = [8u8; 24];
+        let header = VolumeHeader::new(1024, 512, CipherAlgorithm::Aes256Gcm, KdfAlgorithm::Argon2id, KdfParams::argon2id_default(), salt, iv, &KEY).unwrap();
+
+        assert!(header.verify_password(&KEY).unwrap());
+        assert!(!header.verify_password(&[0u8; 32]).unwrap());
+    }
+
+    #[test]
+    fn test_from_bytes_wrong_password() {
+        let salt = [7u8; 32];
+        let iv = [8u8; 24];
+        let header = VolumeHeader::new(1024, 512, CipherAlgorithm::Aes256Gcm, KdfAlgorithm::Argon2id, KdfParams::argon2id_default(), salt, iv, &KEY).unwrap();
+        let bytes = header.to_bytes(&KEY).unwrap();
+
+        let result = VolumeHeader::from_bytes(&bytes, &[0u8; 32]);
+        assert!(matches!(result, Err(HeaderError::WrongPassword)));
+    }
+
+    #[test]
+    fn test_from_bytes_tampered_payload() {
+        let salt = [7u8; 32];
+        let iv = [8u8; 24];
+        let header = VolumeHeader::new(1024, 512, CipherAlgorithm::Aes256Gcm, KdfAlgorithm::Argon2id, KdfParams::argon2id_default(), salt, iv, &KEY).unwrap();
+        let mut bytes = header.to_bytes(&KEY).unwrap();
+
+        // Flip a byte just past the plaintext prefix, inside the encrypted payload
+        let prefix_len = bincode::serialized_size(&HeaderPrefix {
+            magic: MAGIC,
+            version: VERSION,
+            cipher: CipherAlgorithm::Aes256Gcm,
+            kdf: KdfAlgorithm::Argon2id,
+            kdf_params: KdfParams::argon2id_default(),
+            salt,
+            header_iv: iv,
+            payload_nonce: [0u8; 24],
+            verify_tag: [0u8; TAG_LEN],
+        })
+        .unwrap() as usize;
+        bytes[prefix_len] ^= 0xFF;
+
+        let result = VolumeHeader::from_bytes(&bytes, &KEY);
+        assert!(matches!(result, Err(HeaderError::TamperedHeader)));
+    }
 }
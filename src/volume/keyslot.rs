@@ -0,0 +1,489 @@
+//! Key-slot table for unlocking a volume's master key.
+//!
+//! Bulk volume data is always encrypted under one random [`MasterKey`],
+//! generated once at volume-creation time. The master key is never derived
+//! from a password directly; instead it is wrapped by zero or more key
+//! slots, each of which can recover it given different key material. A
+//! password slot wraps the master key under a KDF-derived key, exactly as
+//! before this module existed. A recipient slot wraps it under a key
+//! derived from an ephemeral ECDH exchange (ECIES-style), so a volume can
+//! be shared with someone else's keypair instead of a shared password.
+//! `mount` tries password-derived unwrap first, then each recipient slot
+//! with a supplied private key.
+
+use hkdf::Hkdf;
+use k256::ecdh::diffie_hellman;
+use k256::{PublicKey, SecretKey};
+use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::crypto::aes_gcm::AesGcmEncryptor;
+use crate::crypto::chacha20::{ChaCha20Poly1305Encryptor, XChaCha20Poly1305Encryptor};
+use crate::crypto::Encryptor;
+use crate::volume::header::CipherAlgorithm;
+
+/// Maximum number of key slots a volume can have (mirrors LUKS's 8)
+pub const MAX_KEY_SLOTS: usize = 8;
+
+/// Length of the volume master key in bytes (256-bit)
+pub const MASTER_KEY_LEN: usize = 32;
+
+/// Length of a wrapped master key: the 32-byte key plus a 16-byte AEAD tag
+pub(crate) const WRAPPED_KEY_LEN: usize = MASTER_KEY_LEN + 16;
+
+/// Length of a compressed secp256k1 public key (SEC1 format)
+pub(crate) const PUBKEY_LEN: usize = 33;
+
+/// The volume's bulk-encryption key.
+///
+/// Generated once per volume and wrapped by one or more [`KeySlots`]
+/// entries; it is never derived from a password directly, so revoking a
+/// password or recipient only requires clearing its slot, not
+/// re-encrypting the volume.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct MasterKey([u8; MASTER_KEY_LEN]);
+
+impl std::fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("MasterKey").field(&"<redacted>").finish()
+    }
+}
+
+impl MasterKey {
+    /// Generates a new random master key
+    pub fn generate() -> Self {
+        let mut key = [0u8; MASTER_KEY_LEN];
+        getrandom::fill(&mut key).expect("failed to generate random master key");
+        Self(key)
+    }
+
+    /// Wraps existing key bytes (e.g. one recovered from a key slot) as a `MasterKey`
+    pub fn from_bytes(bytes: [u8; MASTER_KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the raw key bytes
+    pub fn as_bytes(&self) -> &[u8; MASTER_KEY_LEN] {
+        &self.0
+    }
+}
+
+/// Identifies what kind of key material unlocks a given slot
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum KeyKind {
+    /// Slot wraps the master key under a password-derived key
+    Aes = 1,
+
+    /// Slot wraps the master key under an RSA recipient public key
+    ///
+    /// Reserved for future use; [`KeySlots`] does not yet implement wrapping
+    /// or unwrapping for this kind.
+    RsaPublic = 2,
+
+    /// Slot unwraps using an RSA recipient private key
+    ///
+    /// Reserved for future use; [`KeySlots`] does not yet implement wrapping
+    /// or unwrapping for this kind.
+    RsaPrivate = 3,
+
+    /// Slot wraps the master key under a key derived from an ephemeral
+    /// ECDH exchange over secp256k1 (ECIES-style)
+    EcdhSecp256k1 = 4,
+}
+
+/// One entry in the key-slot table.
+///
+/// `ephemeral_public_key` is all-zero and unused for password slots; it
+/// holds the sender's ephemeral public key for recipient slots, so the
+/// holder of the matching private key can redo the ECDH exchange.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct KeySlot {
+    occupied: bool,
+    kind: KeyKind,
+    cipher: CipherAlgorithm,
+    #[serde(with = "BigArray")]
+    nonce: [u8; 24],
+    #[serde(with = "BigArray")]
+    wrapped_key: [u8; WRAPPED_KEY_LEN],
+    #[serde(with = "BigArray")]
+    ephemeral_public_key: [u8; PUBKEY_LEN],
+}
+
+impl KeySlot {
+    const EMPTY: Self = Self {
+        occupied: false,
+        kind: KeyKind::Aes,
+        cipher: CipherAlgorithm::Aes256Gcm,
+        nonce: [0u8; 24],
+        wrapped_key: [0u8; WRAPPED_KEY_LEN],
+        ephemeral_public_key: [0u8; PUBKEY_LEN],
+    };
+}
+
+/// Errors that can occur when wrapping or unwrapping a volume's master key
+#[derive(Debug, Error)]
+pub enum KeySlotError {
+    /// No empty slot was available to add a new key
+    #[error("All {MAX_KEY_SLOTS} key slots are full")]
+    SlotsFull,
+
+    /// None of the occupied slots could be unwrapped with the supplied key material
+    #[error("No key slot matched the supplied password or private key")]
+    NoMatchingSlot,
+
+    /// A SEC1-encoded public or private key was malformed
+    #[error("Invalid secp256k1 key: {0}")]
+    InvalidKey(String),
+
+    /// A kind of slot that isn't implemented yet was requested
+    #[error("Key kind {0:?} is not yet supported")]
+    Unsupported(KeyKind),
+
+    /// Underlying AEAD wrap/unwrap failure
+    #[error("Key wrap/unwrap failed: {0}")]
+    Cryptography(String),
+}
+
+fn aead_encrypt(cipher: CipherAlgorithm, key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, KeySlotError> {
+    let result = match cipher {
+        CipherAlgorithm::Aes256Gcm => AesGcmEncryptor.encrypt(key, nonce, plaintext),
+        CipherAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305Encryptor.encrypt(key, nonce, plaintext),
+        CipherAlgorithm::XChaCha20Poly1305 => XChaCha20Poly1305Encryptor.encrypt(key, nonce, plaintext),
+    };
+    result.map_err(|e| KeySlotError::Cryptography(e.to_string()))
+}
+
+fn aead_decrypt(cipher: CipherAlgorithm, key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, KeySlotError> {
+    let result = match cipher {
+        CipherAlgorithm::Aes256Gcm => AesGcmEncryptor.decrypt(key, nonce, ciphertext),
+        CipherAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305Encryptor.decrypt(key, nonce, ciphertext),
+        CipherAlgorithm::XChaCha20Poly1305 => XChaCha20Poly1305Encryptor.decrypt(key, nonce, ciphertext),
+    };
+    result.map_err(|_| KeySlotError::NoMatchingSlot)
+}
+
+/// Derives a random nonzero secp256k1 secret key
+pub(crate) fn random_secret_key() -> SecretKey {
+    loop {
+        let mut bytes = [0u8; 32];
+        getrandom::fill(&mut bytes).expect("failed to generate random secret key");
+        if let Ok(key) = SecretKey::from_slice(&bytes) {
+            return key;
+        }
+    }
+}
+
+/// HKDF `info` label for deriving a key slot's ECDH wrapping key, mixed with
+/// the ephemeral public key (see [`ecdh_wrap_key`]).
+const WRAP_KEY_INFO: &[u8] = b"tesseract-keyslot-ecdh-wrap-key-v1";
+
+/// Derives the AEAD wrapping key shared by both sides of an ECDH exchange
+/// via HKDF-SHA256, binding the ephemeral public key into the `info`
+/// parameter so the derivation is tied to this specific exchange rather
+/// than just the raw shared secret — a bare `SHA-256(shared_secret)` KDF
+/// (as some ECIES implementations mistakenly use) gives an attacker who
+/// recovers one wrapping key no cryptographic assurance it's bound to the
+/// ephemeral key that produced it.
+pub(crate) fn ecdh_wrap_key(secret: &SecretKey, public: &PublicKey, ephemeral_public_bytes: &[u8]) -> [u8; 32] {
+    let shared = diffie_hellman(secret.to_nonzero_scalar(), public.as_affine());
+    let hkdf = Hkdf::<Sha256>::new(None, shared.raw_secret_bytes());
+
+    let mut info = Vec::with_capacity(WRAP_KEY_INFO.len() + ephemeral_public_bytes.len());
+    info.extend_from_slice(WRAP_KEY_INFO);
+    info.extend_from_slice(ephemeral_public_bytes);
+
+    let mut key = [0u8; 32];
+    hkdf.expand(&info, &mut key)
+        .expect("HKDF-SHA256 expand of a 32-byte output cannot fail");
+    key
+}
+
+/// The full key-slot table for a volume. It is held in memory only:
+/// [`KeySlots`] has no serialization of its own, and
+/// [`VolumeHeader`](crate::volume::header::VolumeHeader) does not carry it.
+#[derive(Debug, Clone)]
+pub struct KeySlots {
+    slots: [KeySlot; MAX_KEY_SLOTS],
+}
+
+impl Default for KeySlots {
+    fn default() -> Self {
+        Self {
+            slots: [KeySlot::EMPTY; MAX_KEY_SLOTS],
+        }
+    }
+}
+
+impl KeySlots {
+    /// Creates an empty key-slot table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn first_empty_slot(&self) -> Result<usize, KeySlotError> {
+        self.slots
+            .iter()
+            .position(|slot| !slot.occupied)
+            .ok_or(KeySlotError::SlotsFull)
+    }
+
+    /// Wraps `master_key` under `kek` (a key derived from a password, as
+    /// with [`crate::crypto::kdf`]) in the first empty slot.
+    ///
+    /// # Returns
+    ///
+    /// The index of the slot the key was stored in
+    pub fn add_password_slot(&mut self, cipher: CipherAlgorithm, kek: &[u8], master_key: &MasterKey) -> Result<usize, KeySlotError> {
+        let index = self.first_empty_slot()?;
+
+        let mut nonce = [0u8; 24];
+        getrandom::fill(&mut nonce[..cipher.nonce_len()]).expect("failed to generate random nonce");
+
+        let sealed = aead_encrypt(cipher, kek, &nonce[..cipher.nonce_len()], master_key.as_bytes())?;
+        let mut wrapped_key = [0u8; WRAPPED_KEY_LEN];
+        wrapped_key.copy_from_slice(&sealed);
+
+        self.slots[index] = KeySlot {
+            occupied: true,
+            kind: KeyKind::Aes,
+            cipher,
+            nonce,
+            wrapped_key,
+            ephemeral_public_key: [0u8; PUBKEY_LEN],
+        };
+
+        Ok(index)
+    }
+
+    /// Wraps `master_key` for `recipient_public_key` via an ephemeral ECDH
+    /// exchange over secp256k1 (ECIES-style), in the first empty slot.
+    ///
+    /// # Returns
+    ///
+    /// The index of the slot the key was stored in
+    pub fn add_recipient_slot(&mut self, cipher: CipherAlgorithm, recipient_public_key: &PublicKey, master_key: &MasterKey) -> Result<usize, KeySlotError> {
+        let index = self.first_empty_slot()?;
+
+        let ephemeral_secret = random_secret_key();
+        let ephemeral_public = ephemeral_secret.public_key();
+        let mut ephemeral_public_key = [0u8; PUBKEY_LEN];
+        ephemeral_public_key.copy_from_slice(&ephemeral_public.to_sec1_bytes());
+        let wrap_key = ecdh_wrap_key(&ephemeral_secret, recipient_public_key, &ephemeral_public_key);
+
+        let mut nonce = [0u8; 24];
+        getrandom::fill(&mut nonce[..cipher.nonce_len()]).expect("failed to generate random nonce");
+
+        let sealed = aead_encrypt(cipher, &wrap_key, &nonce[..cipher.nonce_len()], master_key.as_bytes())?;
+        let mut wrapped_key = [0u8; WRAPPED_KEY_LEN];
+        wrapped_key.copy_from_slice(&sealed);
+
+        self.slots[index] = KeySlot {
+            occupied: true,
+            kind: KeyKind::EcdhSecp256k1,
+            cipher,
+            nonce,
+            wrapped_key,
+            ephemeral_public_key,
+        };
+
+        Ok(index)
+    }
+
+    /// Tries to recover the master key from any occupied password slot
+    /// using `kek`
+    pub fn unwrap_with_password(&self, kek: &[u8]) -> Result<MasterKey, KeySlotError> {
+        for slot in self.slots.iter().filter(|s| s.occupied && s.kind == KeyKind::Aes) {
+            let nonce = &slot.nonce[..slot.cipher.nonce_len()];
+            if let Ok(plaintext) = aead_decrypt(slot.cipher, kek, nonce, &slot.wrapped_key) {
+                let mut key = [0u8; MASTER_KEY_LEN];
+                key.copy_from_slice(&plaintext);
+                return Ok(MasterKey::from_bytes(key));
+            }
+        }
+        Err(KeySlotError::NoMatchingSlot)
+    }
+
+    /// Tries to recover the master key from any occupied recipient slot
+    /// using `private_key`
+    pub fn unwrap_with_private_key(&self, private_key: &SecretKey) -> Result<MasterKey, KeySlotError> {
+        for slot in self.slots.iter().filter(|s| s.occupied && s.kind == KeyKind::EcdhSecp256k1) {
+            let ephemeral_public = match PublicKey::from_sec1_bytes(&slot.ephemeral_public_key) {
+                Ok(pk) => pk,
+                Err(_) => continue,
+            };
+            let wrap_key = ecdh_wrap_key(private_key, &ephemeral_public, &slot.ephemeral_public_key);
+            let nonce = &slot.nonce[..slot.cipher.nonce_len()];
+            if let Ok(plaintext) = aead_decrypt(slot.cipher, &wrap_key, nonce, &slot.wrapped_key) {
+                let mut key = [0u8; MASTER_KEY_LEN];
+                key.copy_from_slice(&plaintext);
+                return Ok(MasterKey::from_bytes(key));
+            }
+        }
+        Err(KeySlotError::NoMatchingSlot)
+    }
+
+    /// Returns the number of occupied slots
+    pub fn occupied_count(&self) -> usize {
+        self.slots.iter().filter(|s| s.occupied).count()
+    }
+
+    /// Wraps `master_key` for `recipient_public_key` exactly as
+    /// [`KeySlots::add_recipient_slot`] does, but returns the wrapped fields
+    /// directly instead of storing them in a slot table. For callers that
+    /// ship a single wrapped key over the wire rather than a full key-slot
+    /// table.
+    pub fn wrap_for_recipient(
+        cipher: CipherAlgorithm,
+        recipient_public_key: &PublicKey,
+        master_key: &MasterKey,
+    ) -> Result<([u8; PUBKEY_LEN], [u8; 24], [u8; WRAPPED_KEY_LEN]), KeySlotError> {
+        let mut slots = KeySlots::new();
+        let index = slots.add_recipient_slot(cipher, recipient_public_key, master_key)?;
+        let slot = &slots.slots[index];
+        Ok((slot.ephemeral_public_key, slot.nonce, slot.wrapped_key))
+    }
+
+    /// Recovers the master key wrapped by [`KeySlots::wrap_for_recipient`],
+    /// given the fields it returned and the matching private key.
+    pub fn unwrap_for_recipient(
+        private_key: &SecretKey,
+        cipher: CipherAlgorithm,
+        ephemeral_public_key: [u8; PUBKEY_LEN],
+        nonce: [u8; 24],
+        wrapped_key: [u8; WRAPPED_KEY_LEN],
+    ) -> Result<MasterKey, KeySlotError> {
+        let mut slots = KeySlots::new();
+        slots.slots[0] = KeySlot {
+            occupied: true,
+            kind: KeyKind::EcdhSecp256k1,
+            cipher,
+            nonce,
+            wrapped_key,
+            ephemeral_public_key,
+        };
+        slots.unwrap_with_private_key(private_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_slot_roundtrip() {
+        let mut slots = KeySlots::new();
+        let master_key = MasterKey::generate();
+        let kek = [1u8; 32];
+
+        let index = slots.add_password_slot(CipherAlgorithm::Aes256Gcm, &kek, &master_key).unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(slots.occupied_count(), 1);
+
+        let recovered = slots.unwrap_with_password(&kek).unwrap();
+        assert_eq!(recovered.as_bytes(), master_key.as_bytes());
+    }
+
+    #[test]
+    fn test_password_slot_wrong_kek() {
+        let mut slots = KeySlots::new();
+        let master_key = MasterKey::generate();
+        slots.add_password_slot(CipherAlgorithm::Aes256Gcm, &[1u8; 32], &master_key).unwrap();
+
+        let result = slots.unwrap_with_password(&[2u8; 32]);
+        assert!(matches!(result, Err(KeySlotError::NoMatchingSlot)));
+    }
+
+    #[test]
+    fn test_recipient_slot_roundtrip() {
+        let mut slots = KeySlots::new();
+        let master_key = MasterKey::generate();
+        let recipient_secret = random_secret_key();
+        let recipient_public = recipient_secret.public_key();
+
+        slots.add_recipient_slot(CipherAlgorithm::Aes256Gcm, &recipient_public, &master_key).unwrap();
+
+        let recovered = slots.unwrap_with_private_key(&recipient_secret).unwrap();
+        assert_eq!(recovered.as_bytes(), master_key.as_bytes());
+    }
+
+    #[test]
+    fn test_recipient_slot_wrong_private_key() {
+        let mut slots = KeySlots::new();
+        let master_key = MasterKey::generate();
+        let recipient_public = random_secret_key().public_key();
+        slots.add_recipient_slot(CipherAlgorithm::Aes256Gcm, &recipient_public, &master_key).unwrap();
+
+        let result = slots.unwrap_with_private_key(&random_secret_key());
+        assert!(matches!(result, Err(KeySlotError::NoMatchingSlot)));
+    }
+
+    #[test]
+    fn test_slots_full() {
+        let mut slots = KeySlots::new();
+        let master_key = MasterKey::generate();
+        for _ in 0..MAX_KEY_SLOTS {
+            slots.add_password_slot(CipherAlgorithm::Aes256Gcm, &[3u8; 32], &master_key).unwrap();
+        }
+
+        let result = slots.add_password_slot(CipherAlgorithm::Aes256Gcm, &[3u8; 32], &master_key);
+        assert!(matches!(result, Err(KeySlotError::SlotsFull)));
+    }
+
+    #[test]
+    fn test_mixed_slots_coexist() {
+        let mut slots = KeySlots::new();
+        let master_key = MasterKey::generate();
+        let kek = [4u8; 32];
+        let recipient_secret = random_secret_key();
+        let recipient_public = recipient_secret.public_key();
+
+        slots.add_password_slot(CipherAlgorithm::Aes256Gcm, &kek, &master_key).unwrap();
+        slots.add_recipient_slot(CipherAlgorithm::ChaCha20Poly1305, &recipient_public, &master_key).unwrap();
+        assert_eq!(slots.occupied_count(), 2);
+
+        assert_eq!(slots.unwrap_with_password(&kek).unwrap().as_bytes(), master_key.as_bytes());
+        assert_eq!(slots.unwrap_with_private_key(&recipient_secret).unwrap().as_bytes(), master_key.as_bytes());
+    }
+
+    #[test]
+    fn test_wrap_unwrap_for_recipient_roundtrip() {
+        let master_key = MasterKey::generate();
+        let recipient_secret = random_secret_key();
+        let recipient_public = recipient_secret.public_key();
+
+        let (ephemeral_public_key, nonce, wrapped_key) =
+            KeySlots::wrap_for_recipient(CipherAlgorithm::Aes256Gcm, &recipient_public, &master_key).unwrap();
+
+        let recovered = KeySlots::unwrap_for_recipient(
+            &recipient_secret,
+            CipherAlgorithm::Aes256Gcm,
+            ephemeral_public_key,
+            nonce,
+            wrapped_key,
+        )
+        .unwrap();
+        assert_eq!(recovered.as_bytes(), master_key.as_bytes());
+    }
+
+    #[test]
+    fn test_wrap_for_recipient_wrong_private_key_fails() {
+        let master_key = MasterKey::generate();
+        let recipient_public = random_secret_key().public_key();
+
+        let (ephemeral_public_key, nonce, wrapped_key) =
+            KeySlots::wrap_for_recipient(CipherAlgorithm::Aes256Gcm, &recipient_public, &master_key).unwrap();
+
+        let result = KeySlots::unwrap_for_recipient(
+            &random_secret_key(),
+            CipherAlgorithm::Aes256Gcm,
+            ephemeral_public_key,
+            nonce,
+            wrapped_key,
+        );
+        assert!(matches!(result, Err(KeySlotError::NoMatchingSlot)));
+    }
+}
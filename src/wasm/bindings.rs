@@ -3,11 +3,109 @@
 use wasm_bindgen::prelude::*;
 use serde::{Serialize, Deserialize};
 use base64::Engine;
+use zeroize::Zeroizing;
 
 use crate::crypto::{Encryptor, KeyDerivation};
 use crate::crypto::aes_gcm::AesGcmEncryptor;
+use crate::crypto::chacha20::{ChaCha20Poly1305Encryptor, XChaCha20Poly1305Encryptor};
 use crate::crypto::kdf::Argon2Kdf;
+use crate::crypto::kdf_alt::{Pbkdf2Kdf, ScryptKdf};
 use crate::config::CryptoConfig;
+use crate::volume::header::{CipherAlgorithm, KdfAlgorithm};
+use crate::volume::keyslot::{KeySlots, MasterKey, PUBKEY_LEN, WRAPPED_KEY_LEN};
+use k256::{PublicKey as EcPublicKey, SecretKey as EcSecretKey};
+
+/// Sane default `(memory_cost, time_cost)` for each KDF, used to reset
+/// [`EncryptConfig`]'s cost parameters whenever [`EncryptConfig::with_kdf`]
+/// switches algorithm, so a cost tuned for one KDF is never silently reused
+/// as a (far too weak) cost for another:
+/// - Argon2id: 64 MiB memory, 3 iterations
+/// - PBKDF2-HMAC-SHA256: 600,000 iterations (OWASP 2023 recommendation)
+/// - scrypt: `log2(N) = 17`, i.e. `N = 131072`
+fn default_costs_for_kdf(kdf: KdfAlgorithm) -> (u32, u32) {
+    match kdf {
+        KdfAlgorithm::Argon2id => (65536, 3),
+        KdfAlgorithm::Pbkdf2HmacSha256 => (0, 600_000),
+        KdfAlgorithm::Scrypt => (0, 17),
+    }
+}
+
+/// Derives a key using the KDF selected by `kdf`, interpreting `memory_cost`
+/// and `time_cost` according to that algorithm:
+/// - Argon2id: `memory_cost` KiB, `time_cost` iterations
+/// - PBKDF2-HMAC-SHA256: `time_cost` iterations (`memory_cost` unused)
+/// - scrypt: `time_cost` as `log2(N)`, fixed `r=8, p=1` (`memory_cost` unused)
+///
+/// Every caller in this module funnels through here, so wrapping the result
+/// in [`Zeroizing`] at this one chokepoint is enough to guarantee the
+/// derived key is wiped from WASM linear memory as soon as the caller drops
+/// it, without reimplementing that guarantee at every call site.
+fn derive_key_with_kdf(
+    kdf: KdfAlgorithm,
+    memory_cost: u32,
+    time_cost: u32,
+    password: &[u8],
+    salt: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, JsValue> {
+    let result = match kdf {
+        KdfAlgorithm::Argon2id => {
+            let crypto_config = CryptoConfig {
+                argon2_mem_cost_kib: memory_cost,
+                argon2_time_cost: time_cost,
+                argon2_lanes: 1,
+            };
+            Argon2Kdf::new(crypto_config).derive_key(password, salt)
+        }
+        KdfAlgorithm::Pbkdf2HmacSha256 => Pbkdf2Kdf::new(time_cost).derive_key(password, salt),
+        KdfAlgorithm::Scrypt => ScryptKdf::new(time_cost as u8, 8, 1).derive_key(password, salt),
+    };
+    result
+        .map(Zeroizing::new)
+        .map_err(|e| JsValue::from_str(&format!("Key derivation failed: {}", e)))
+}
+
+/// Encrypts `plaintext` with the cipher selected by `cipher`, dispatching to
+/// the matching AEAD implementation at runtime.
+fn encrypt_with_cipher(
+    cipher: CipherAlgorithm,
+    key: &[u8],
+    nonce: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let result = match cipher {
+        CipherAlgorithm::Aes256Gcm => AesGcmEncryptor.encrypt(key, nonce, plaintext),
+        CipherAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305Encryptor.encrypt(key, nonce, plaintext),
+        CipherAlgorithm::XChaCha20Poly1305 => XChaCha20Poly1305Encryptor.encrypt(key, nonce, plaintext),
+    };
+    result.map_err(|e| JsValue::from_str(&format!("Encryption failed: {}", e)))
+}
+
+/// Decrypts `ciphertext` with the cipher selected by `cipher`, dispatching to
+/// the matching AEAD implementation at runtime.
+///
+/// Returns the plaintext wrapped in [`Zeroizing`], same as
+/// [`derive_key_with_kdf`] does for derived keys, so every caller funnels
+/// the recovered plaintext through one chokepoint that wipes it from WASM
+/// linear memory as soon as the caller's copy (or conversion into a
+/// `String`/returned `Vec<u8>`) drops it. Once a caller hands the bytes
+/// across the WASM/JS boundary (e.g. [`decrypt_bytes_with_config`]'s
+/// return), the copy living in JS memory is outside Rust's control and
+/// can't be zeroized from here.
+fn decrypt_with_cipher(
+    cipher: CipherAlgorithm,
+    key: &[u8],
+    nonce: &[u8],
+    ciphertext: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, JsValue> {
+    let result = match cipher {
+        CipherAlgorithm::Aes256Gcm => AesGcmEncryptor.decrypt(key, nonce, ciphertext),
+        CipherAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305Encryptor.decrypt(key, nonce, ciphertext),
+        CipherAlgorithm::XChaCha20Poly1305 => XChaCha20Poly1305Encryptor.decrypt(key, nonce, ciphertext),
+    };
+    result
+        .map(Zeroizing::new)
+        .map_err(|e| JsValue::from_str(&format!("Decryption failed: {}", e)))
+}
 
 /// Initialize the WASM module
 ///
@@ -24,12 +122,14 @@ pub fn init() {
 #[wasm_bindgen]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptConfig {
-    /// Use Argon2 for key derivation (recommended)
-    use_argon2: bool,
-    /// Argon2 memory cost in KB (default: 65536 = 64MB)
+    /// Key-derivation function used to turn the password into a key
+    kdf: KdfAlgorithm,
+    /// KDF memory cost in KB, meaningful for Argon2id only (default: 65536 = 64MB)
     memory_cost: u32,
-    /// Argon2 time cost (iterations, default: 3)
+    /// KDF time cost (iterations for Argon2id/PBKDF2, log2(N) for scrypt, default: 3)
     time_cost: u32,
+    /// Cipher algorithm used for encryption (default: AES-256-GCM)
+    cipher: CipherAlgorithm,
 }
 
 #[wasm_bindgen]
@@ -44,9 +144,10 @@ impl EncryptConfig {
     #[wasm_bindgen]
     pub fn fast() -> Self {
         Self {
-            use_argon2: true,
+            kdf: KdfAlgorithm::Argon2id,
             memory_cost: 8192,  // 8MB
             time_cost: 1,
+            cipher: CipherAlgorithm::Aes256Gcm,
         }
     }
 
@@ -60,19 +161,43 @@ impl EncryptConfig {
     #[wasm_bindgen]
     pub fn secure() -> Self {
         Self {
-            use_argon2: true,
+            kdf: KdfAlgorithm::Argon2id,
             memory_cost: 131072,  // 128MB
             time_cost: 5,
+            cipher: CipherAlgorithm::Aes256Gcm,
         }
     }
+
+    /// Selects the cipher algorithm to use for encryption
+    #[wasm_bindgen]
+    pub fn with_cipher(mut self, cipher: CipherAlgorithm) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    /// Selects the key-derivation function to use for encryption
+    ///
+    /// `memory_cost`/`time_cost` are reset to sane defaults for the chosen
+    /// algorithm, since the two fields mean different things per KDF (e.g.
+    /// Argon2id's default `time_cost` of 3 is a catastrophically weak
+    /// PBKDF2 iteration count).
+    #[wasm_bindgen]
+    pub fn with_kdf(mut self, kdf: KdfAlgorithm) -> Self {
+        let (memory_cost, time_cost) = default_costs_for_kdf(kdf);
+        self.kdf = kdf;
+        self.memory_cost = memory_cost;
+        self.time_cost = time_cost;
+        self
+    }
 }
 
 impl Default for EncryptConfig {
     fn default() -> Self {
         Self {
-            use_argon2: true,
+            kdf: KdfAlgorithm::Argon2id,
             memory_cost: 65536,  // 64MB
             time_cost: 3,
+            cipher: CipherAlgorithm::Aes256Gcm,
         }
     }
 }
@@ -116,36 +241,29 @@ pub fn encrypt_text_with_config(
     plaintext: &str,
     config: &EncryptConfig,
 ) -> Result<String, JsValue> {
-    // Create Argon2 KDF with custom parameters
-    let crypto_config = CryptoConfig {
-        argon2_mem_cost_kib: config.memory_cost,
-        argon2_time_cost: config.time_cost,
-        argon2_lanes: 1,
-    };
-    let kdf = Argon2Kdf::new(crypto_config);
-
     // Generate salt
-    let salt = kdf.generate_salt();
+    let mut salt = [0u8; 32];
+    getrandom::fill(&mut salt)
+        .map_err(|e| JsValue::from_str(&format!("Random generation failed: {}", e)))?;
 
-    // Derive key from password
-    let key = kdf
-        .derive_key(password.as_bytes(), &salt)
-        .map_err(|e| JsValue::from_str(&format!("Key derivation failed: {}", e)))?;
+    // Derive key from password with the configured KDF
+    let key = derive_key_with_kdf(config.kdf, config.memory_cost, config.time_cost, password.as_bytes(), &salt)?;
 
-    // Generate random nonce
-    let mut nonce = [0u8; 12];
+    // Generate a random nonce sized for the selected cipher
+    let mut nonce = vec![0u8; config.cipher.nonce_len()];
     getrandom::fill(&mut nonce)
         .map_err(|e| JsValue::from_str(&format!("Random generation failed: {}", e)))?;
 
-    // Encrypt
-    let encryptor = AesGcmEncryptor;
-    let ciphertext = encryptor
-        .encrypt(&*key, &nonce, plaintext.as_bytes())
-        .map_err(|e| JsValue::from_str(&format!("Encryption failed: {}", e)))?;
+    // Encrypt with the configured cipher
+    let ciphertext = encrypt_with_cipher(config.cipher, &key, &nonce, plaintext.as_bytes())?;
 
-    // Combine: salt || nonce || ciphertext
+    // Combine: kdf_id || memory_cost || time_cost || salt || cipher_id || nonce || ciphertext
     let mut result = Vec::new();
+    result.push(config.kdf as u8);
+    result.extend_from_slice(&config.memory_cost.to_le_bytes());
+    result.extend_from_slice(&config.time_cost.to_le_bytes());
     result.extend_from_slice(&salt);
+    result.push(config.cipher as u8);
     result.extend_from_slice(&nonce);
     result.extend_from_slice(&ciphertext);
 
@@ -181,7 +299,9 @@ pub fn decrypt_text(password: &str, encrypted_base64: &str) -> Result<String, Js
 ///
 /// * `password` - The password used for encryption
 /// * `encrypted_base64` - Base64-encoded encrypted data
-/// * `config` - Encryption configuration (must match encryption config)
+/// * `_config` - Unused; kept for API symmetry with `encrypt_text_with_config`.
+///   The KDF, its parameters, and the cipher are all read back out of the
+///   encrypted blob, so decryption no longer needs a matching config.
 ///
 /// # Returns
 ///
@@ -190,49 +310,51 @@ pub fn decrypt_text(password: &str, encrypted_base64: &str) -> Result<String, Js
 pub fn decrypt_text_with_config(
     password: &str,
     encrypted_base64: &str,
-    config: &EncryptConfig,
+    _config: &EncryptConfig,
 ) -> Result<String, JsValue> {
     // Decode base64
     let data = base64::engine::general_purpose::STANDARD
         .decode(encrypted_base64)
         .map_err(|e| JsValue::from_str(&format!("Base64 decode failed: {}", e)))?;
 
-    // Extract: salt || nonce || ciphertext
-    // Salt size is determined by the KDF (typically 32 bytes for Argon2)
-    let salt_size = 32;
-    if data.len() < salt_size + 12 {
+    let plaintext = decrypt_self_describing(password, &data)?;
+
+    // Convert to string; `plaintext` itself is zeroized once this scope ends.
+    String::from_utf8(plaintext.to_vec())
+        .map_err(|e| JsValue::from_str(&format!("UTF-8 decode failed: {}", e)))
+}
+
+/// Decrypts a blob produced by `encrypt_text_with_config`/`encrypt_bytes_with_config`,
+/// reading the KDF, its parameters, and the cipher back out of the blob itself.
+///
+/// Layout: `kdf_id || memory_cost (u32 LE) || time_cost (u32 LE) || salt (32
+/// bytes) || cipher_id || nonce || ciphertext`.
+fn decrypt_self_describing(password: &str, data: &[u8]) -> Result<Zeroizing<Vec<u8>>, JsValue> {
+    const PREFIX_LEN: usize = 1 + 4 + 4 + 32;
+    if data.len() < PREFIX_LEN + 1 {
         return Err(JsValue::from_str("Invalid encrypted data"));
     }
 
-    let (salt, rest) = data.split_at(salt_size);
-    let (nonce, ciphertext) = rest.split_at(12);
+    let kdf = KdfAlgorithm::try_from(data[0])
+        .map_err(|e| JsValue::from_str(&format!("Invalid KDF id: {}", e)))?;
+    let memory_cost = u32::from_le_bytes(data[1..5].try_into().unwrap());
+    let time_cost = u32::from_le_bytes(data[5..9].try_into().unwrap());
+    let salt = &data[9..PREFIX_LEN];
+    let rest = &data[PREFIX_LEN..];
 
-    let nonce: [u8; 12] = nonce
-        .try_into()
-        .map_err(|_| JsValue::from_str("Invalid nonce"))?;
+    let (cipher_id, rest) = rest.split_first()
+        .ok_or_else(|| JsValue::from_str("Invalid encrypted data"))?;
+    let cipher = CipherAlgorithm::try_from(*cipher_id)
+        .map_err(|e| JsValue::from_str(&format!("Invalid cipher id: {}", e)))?;
 
-    // Create Argon2 KDF with custom parameters
-    let crypto_config = CryptoConfig {
-        argon2_mem_cost_kib: config.memory_cost,
-        argon2_time_cost: config.time_cost,
-        argon2_lanes: 1,
-    };
-    let kdf = Argon2Kdf::new(crypto_config);
-
-    // Derive key from password
-    let key = kdf
-        .derive_key(password.as_bytes(), salt)
-        .map_err(|e| JsValue::from_str(&format!("Key derivation failed: {}", e)))?;
+    if rest.len() < cipher.nonce_len() {
+        return Err(JsValue::from_str("Invalid encrypted data"));
+    }
+    let (nonce, ciphertext) = rest.split_at(cipher.nonce_len());
 
-    // Decrypt
-    let encryptor = AesGcmEncryptor;
-    let plaintext = encryptor
-        .decrypt(&*key, &nonce, ciphertext)
-        .map_err(|e| JsValue::from_str(&format!("Decryption failed: {}", e)))?;
+    let key = derive_key_with_kdf(kdf, memory_cost, time_cost, password.as_bytes(), salt)?;
 
-    // Convert to string
-    String::from_utf8(plaintext)
-        .map_err(|e| JsValue::from_str(&format!("UTF-8 decode failed: {}", e)))
+    decrypt_with_cipher(cipher, &key, nonce, ciphertext)
 }
 
 /// Encrypt binary data with a password
@@ -244,7 +366,7 @@ pub fn decrypt_text_with_config(
 ///
 /// # Returns
 ///
-/// Encrypted data (salt || nonce || ciphertext)
+/// Encrypted data (kdf_id || memory_cost || time_cost || salt || cipher_id || nonce || ciphertext)
 #[wasm_bindgen]
 pub fn encrypt_bytes(password: &str, data: &[u8]) -> Result<Vec<u8>, JsValue> {
     encrypt_bytes_with_config(password, data, &EncryptConfig::default())
@@ -257,36 +379,29 @@ pub fn encrypt_bytes_with_config(
     data: &[u8],
     config: &EncryptConfig,
 ) -> Result<Vec<u8>, JsValue> {
-    // Create Argon2 KDF with custom parameters
-    let crypto_config = CryptoConfig {
-        argon2_mem_cost_kib: config.memory_cost,
-        argon2_time_cost: config.time_cost,
-        argon2_lanes: 1,
-    };
-    let kdf = Argon2Kdf::new(crypto_config);
-
     // Generate salt
-    let salt = kdf.generate_salt();
+    let mut salt = [0u8; 32];
+    getrandom::fill(&mut salt)
+        .map_err(|e| JsValue::from_str(&format!("Random generation failed: {}", e)))?;
 
-    // Derive key from password
-    let key = kdf
-        .derive_key(password.as_bytes(), &salt)
-        .map_err(|e| JsValue::from_str(&format!("Key derivation failed: {}", e)))?;
+    // Derive key from password with the configured KDF
+    let key = derive_key_with_kdf(config.kdf, config.memory_cost, config.time_cost, password.as_bytes(), &salt)?;
 
-    // Generate random nonce
-    let mut nonce = [0u8; 12];
+    // Generate a random nonce sized for the selected cipher
+    let mut nonce = vec![0u8; config.cipher.nonce_len()];
     getrandom::fill(&mut nonce)
         .map_err(|e| JsValue::from_str(&format!("Random generation failed: {}", e)))?;
 
-    // Encrypt
-    let encryptor = AesGcmEncryptor;
-    let ciphertext = encryptor
-        .encrypt(&*key, &nonce, data)
-        .map_err(|e| JsValue::from_str(&format!("Encryption failed: {}", e)))?;
+    // Encrypt with the configured cipher
+    let ciphertext = encrypt_with_cipher(config.cipher, &key, &nonce, data)?;
 
-    // Combine: salt || nonce || ciphertext
+    // Combine: kdf_id || memory_cost || time_cost || salt || cipher_id || nonce || ciphertext
     let mut result = Vec::new();
+    result.push(config.kdf as u8);
+    result.extend_from_slice(&config.memory_cost.to_le_bytes());
+    result.extend_from_slice(&config.time_cost.to_le_bytes());
     result.extend_from_slice(&salt);
+    result.push(config.cipher as u8);
     result.extend_from_slice(&nonce);
     result.extend_from_slice(&ciphertext);
 
@@ -298,7 +413,7 @@ pub fn encrypt_bytes_with_config(
 /// # Arguments
 ///
 /// * `password` - The password used for encryption
-/// * `encrypted_data` - Encrypted data (salt || nonce || ciphertext)
+/// * `encrypted_data` - Encrypted data (kdf_id || memory_cost || time_cost || salt || cipher_id || nonce || ciphertext)
 ///
 /// # Returns
 ///
@@ -309,43 +424,233 @@ pub fn decrypt_bytes(password: &str, encrypted_data: &[u8]) -> Result<Vec<u8>, J
 }
 
 /// Decrypt binary data with custom configuration
+///
+/// `_config` is unused; kept for API symmetry with `encrypt_bytes_with_config`.
+/// The KDF, its parameters, and the cipher are all read back out of the
+/// encrypted blob, so decryption no longer needs a matching config.
 #[wasm_bindgen]
 pub fn decrypt_bytes_with_config(
     password: &str,
     encrypted_data: &[u8],
-    config: &EncryptConfig,
+    _config: &EncryptConfig,
 ) -> Result<Vec<u8>, JsValue> {
-    // Extract: salt || nonce || ciphertext
-    let salt_size = 32;
-    if encrypted_data.len() < salt_size + 12 {
-        return Err(JsValue::from_str("Invalid encrypted data"));
+    // The returned copy crosses into JS-owned memory and can't be zeroized
+    // from here; `decrypt_self_describing`'s own `Zeroizing` buffer is wiped
+    // once this function returns.
+    Ok(decrypt_self_describing(password, encrypted_data)?.to_vec())
+}
+
+/// Builds the streaming session header: `kdf_id || memory_cost (u32 LE)
+/// || time_cost (u32 LE) || salt (32 bytes) || cipher_id || base_nonce`
+fn stream_header_bytes(kdf: KdfAlgorithm, memory_cost: u32, time_cost: u32, salt: &[u8; 32], cipher: CipherAlgorithm, base_nonce: &[u8]) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.push(kdf as u8);
+    header.extend_from_slice(&memory_cost.to_le_bytes());
+    header.extend_from_slice(&time_cost.to_le_bytes());
+    header.extend_from_slice(salt);
+    header.push(cipher as u8);
+    header.extend_from_slice(base_nonce);
+    header
+}
+
+/// Parses a streaming session header produced by [`stream_header_bytes`],
+/// deriving the key along the way.
+fn parse_stream_header(password: &str, header: &[u8]) -> Result<(CipherAlgorithm, Zeroizing<Vec<u8>>, Vec<u8>), JsValue> {
+    const PREFIX_LEN: usize = 1 + 4 + 4 + 32;
+    if header.len() < PREFIX_LEN + 1 {
+        return Err(JsValue::from_str("Invalid stream header"));
     }
 
-    let (salt, rest) = encrypted_data.split_at(salt_size);
-    let (nonce, ciphertext) = rest.split_at(12);
+    let kdf = KdfAlgorithm::try_from(header[0]).map_err(|e| JsValue::from_str(&format!("Invalid KDF id: {}", e)))?;
+    let memory_cost = u32::from_le_bytes(header[1..5].try_into().unwrap());
+    let time_cost = u32::from_le_bytes(header[5..9].try_into().unwrap());
+    let salt = &header[9..PREFIX_LEN];
 
-    let nonce: [u8; 12] = nonce
-        .try_into()
-        .map_err(|_| JsValue::from_str("Invalid nonce"))?;
+    let (cipher_id, base_nonce) = header[PREFIX_LEN..]
+        .split_first()
+        .ok_or_else(|| JsValue::from_str("Invalid stream header"))?;
+    let cipher = CipherAlgorithm::try_from(*cipher_id).map_err(|e| JsValue::from_str(&format!("Invalid cipher id: {}", e)))?;
 
-    // Create Argon2 KDF with custom parameters
-    let crypto_config = CryptoConfig {
-        argon2_mem_cost_kib: config.memory_cost,
-        argon2_time_cost: config.time_cost,
-        argon2_lanes: 1,
-    };
-    let kdf = Argon2Kdf::new(crypto_config);
+    let key = derive_key_with_kdf(kdf, memory_cost, time_cost, password.as_bytes(), salt)?;
 
-    // Derive key from password
-    let key = kdf
-        .derive_key(password.as_bytes(), salt)
-        .map_err(|e| JsValue::from_str(&format!("Key derivation failed: {}", e)))?;
+    Ok((cipher, key, base_nonce.to_vec()))
+}
 
-    // Decrypt
-    let encryptor = AesGcmEncryptor;
-    encryptor
-        .decrypt(&*key, &nonce, ciphertext)
-        .map_err(|e| JsValue::from_str(&format!("Decryption failed: {}", e)))
+/// A chunked streaming encryption session for input too large to buffer in
+/// full. Call `encrypt_chunk` for every chunk but the last, then `finish` on
+/// the last chunk; `header_bytes` must be stored ahead of the sealed chunks
+/// so a matching `StreamDecryptSession` can be constructed.
+#[wasm_bindgen]
+pub struct StreamEncryptSession {
+    inner: crate::crypto::streaming::StreamEncryptor,
+    header_bytes: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl StreamEncryptSession {
+    /// Starts a new streaming encryption session, deriving a key from
+    /// `password` and generating a random salt and base nonce.
+    #[wasm_bindgen(constructor)]
+    pub fn new(password: &str, config: &EncryptConfig) -> Result<StreamEncryptSession, JsValue> {
+        let mut salt = [0u8; 32];
+        getrandom::fill(&mut salt)
+            .map_err(|e| JsValue::from_str(&format!("Random generation failed: {}", e)))?;
+
+        let key = derive_key_with_kdf(config.kdf, config.memory_cost, config.time_cost, password.as_bytes(), &salt)?;
+
+        let mut base_nonce = vec![0u8; config.cipher.nonce_len() - crate::crypto::streaming::STREAM_COUNTER_LEN];
+        getrandom::fill(&mut base_nonce)
+            .map_err(|e| JsValue::from_str(&format!("Random generation failed: {}", e)))?;
+
+        let header_bytes = stream_header_bytes(config.kdf, config.memory_cost, config.time_cost, &salt, config.cipher, &base_nonce);
+
+        let inner = crate::crypto::streaming::StreamEncryptor::new(config.cipher, key, base_nonce)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        Ok(Self { inner, header_bytes })
+    }
+
+    /// Returns the header bytes that must be stored ahead of the sealed
+    /// chunks so the stream can later be decrypted
+    pub fn header_bytes(&self) -> Vec<u8> {
+        self.header_bytes.clone()
+    }
+
+    /// Seals the next chunk, which is not the last chunk of the stream
+    pub fn encrypt_chunk(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.inner.encrypt_chunk(plaintext).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Seals the final chunk of the stream. Consumes the session, so it
+    /// must be the last call made on this object.
+    pub fn finish(self, plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.inner.finish(plaintext).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+/// The decrypting counterpart to [`StreamEncryptSession`]
+#[wasm_bindgen]
+pub struct StreamDecryptSession {
+    inner: crate::crypto::streaming::StreamDecryptor,
+}
+
+#[wasm_bindgen]
+impl StreamDecryptSession {
+    /// Starts a new streaming decryption session from `password` and the
+    /// header bytes produced by [`StreamEncryptSession::header_bytes`]
+    #[wasm_bindgen(constructor)]
+    pub fn new(password: &str, header_bytes: &[u8]) -> Result<StreamDecryptSession, JsValue> {
+        let (cipher, key, base_nonce) = parse_stream_header(password, header_bytes)?;
+        let inner = crate::crypto::streaming::StreamDecryptor::new(cipher, key, base_nonce)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    /// Opens the next non-final chunk of the stream
+    pub fn decrypt_chunk(&mut self, sealed: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.inner.decrypt_chunk(sealed).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Opens the final chunk of the stream. Consumes the session, and fails
+    /// if `sealed` was not actually sealed as the stream's final chunk
+    /// (e.g. because an attacker truncated the stream).
+    pub fn finish(self, sealed: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.inner.finish(sealed).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+/// Encrypts `data` to a recipient's secp256k1 public key instead of a
+/// shared password, so a volume can be unlocked by whoever holds the
+/// matching private key. A random master key encrypts `data`; an ephemeral
+/// ECDH exchange (ECIES-style) wraps that master key for the recipient.
+///
+/// Layout: `cipher_id || ephemeral_pubkey (33 bytes) || wrap_nonce
+/// (cipher.nonce_len() bytes) || wrapped_key (48 bytes) || data_nonce
+/// (cipher.nonce_len() bytes) || ciphertext`
+///
+/// # Arguments
+///
+/// * `recipient_public_key` - SEC1-encoded (compressed) secp256k1 public key
+/// * `data` - Plaintext to encrypt
+/// * `cipher` - Cipher used for both key-wrapping and data encryption
+#[wasm_bindgen]
+pub fn encrypt_to_pubkey(recipient_public_key: &[u8], data: &[u8], cipher: CipherAlgorithm) -> Result<Vec<u8>, JsValue> {
+    let recipient = EcPublicKey::from_sec1_bytes(recipient_public_key)
+        .map_err(|e| JsValue::from_str(&format!("Invalid recipient public key: {}", e)))?;
+
+    let master_key = MasterKey::generate();
+
+    let (ephemeral_public_key, wrap_nonce, wrapped_key) = KeySlots::wrap_for_recipient(cipher, &recipient, &master_key)
+        .map_err(|e| JsValue::from_str(&format!("Key wrapping failed: {}", e)))?;
+    let wrap_nonce = &wrap_nonce[..cipher.nonce_len()];
+
+    let mut data_nonce = vec![0u8; cipher.nonce_len()];
+    getrandom::fill(&mut data_nonce)
+        .map_err(|e| JsValue::from_str(&format!("Random generation failed: {}", e)))?;
+    let ciphertext = encrypt_with_cipher(cipher, master_key.as_bytes(), &data_nonce, data)?;
+
+    let mut result = Vec::new();
+    result.push(cipher as u8);
+    result.extend_from_slice(&ephemeral_public_key);
+    result.extend_from_slice(wrap_nonce);
+    result.extend_from_slice(&wrapped_key);
+    result.extend_from_slice(&data_nonce);
+    result.extend_from_slice(&ciphertext);
+
+    Ok(result)
+}
+
+/// Decrypts a blob produced by [`encrypt_to_pubkey`] using the matching
+/// secp256k1 private key.
+///
+/// # Arguments
+///
+/// * `private_key` - Raw 32-byte secp256k1 private key
+/// * `data` - Encrypted blob produced by `encrypt_to_pubkey`
+#[wasm_bindgen]
+pub fn decrypt_with_privkey(private_key: &[u8], data: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let secret = EcSecretKey::from_slice(private_key)
+        .map_err(|e| JsValue::from_str(&format!("Invalid private key: {}", e)))?;
+
+    let (cipher_id, rest) = data.split_first().ok_or_else(|| JsValue::from_str("Invalid encrypted data"))?;
+    let cipher = CipherAlgorithm::try_from(*cipher_id).map_err(|e| JsValue::from_str(&format!("Invalid cipher id: {}", e)))?;
+
+    if rest.len() < PUBKEY_LEN {
+        return Err(JsValue::from_str("Invalid encrypted data"));
+    }
+    let (ephemeral_public_bytes, rest) = rest.split_at(PUBKEY_LEN);
+    EcPublicKey::from_sec1_bytes(ephemeral_public_bytes)
+        .map_err(|e| JsValue::from_str(&format!("Invalid ephemeral public key: {}", e)))?;
+
+    if rest.len() < cipher.nonce_len() {
+        return Err(JsValue::from_str("Invalid encrypted data"));
+    }
+    let (wrap_nonce, rest) = rest.split_at(cipher.nonce_len());
+
+    if rest.len() < WRAPPED_KEY_LEN {
+        return Err(JsValue::from_str("Invalid encrypted data"));
+    }
+    let (wrapped_key, rest) = rest.split_at(WRAPPED_KEY_LEN);
+
+    if rest.len() < cipher.nonce_len() {
+        return Err(JsValue::from_str("Invalid encrypted data"));
+    }
+    let (data_nonce, ciphertext) = rest.split_at(cipher.nonce_len());
+
+    let mut ephemeral_public_key = [0u8; PUBKEY_LEN];
+    ephemeral_public_key.copy_from_slice(ephemeral_public_bytes);
+    let mut nonce = [0u8; 24];
+    nonce[..wrap_nonce.len()].copy_from_slice(wrap_nonce);
+    let mut wrapped_key_buf = [0u8; WRAPPED_KEY_LEN];
+    wrapped_key_buf.copy_from_slice(wrapped_key);
+
+    let master_key = KeySlots::unwrap_for_recipient(&secret, cipher, ephemeral_public_key, nonce, wrapped_key_buf)
+        .map_err(|e| JsValue::from_str(&format!("Key unwrapping failed: {}", e)))?;
+
+    // The returned copy crosses into JS-owned memory and can't be zeroized
+    // from here; `decrypt_with_cipher`'s own `Zeroizing` buffer is wiped
+    // once this function returns.
+    Ok(decrypt_with_cipher(cipher, master_key.as_bytes(), data_nonce, ciphertext)?.to_vec())
 }
 
 /// Get version information